@@ -0,0 +1,381 @@
+//! Admission control for incoming connections.
+//!
+//! These checks are cheap enough to run in a listener callback, before the
+//! handshake completes and any per-connection state is allocated.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+use crate::reject::RejectReason;
+
+/// An IP network in CIDR notation, such as `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Creates a network from an address and prefix length, returning `None`
+    /// if the prefix is longer than the address.
+    pub fn new(addr: IpAddr, prefix: u8) -> Option<Self> {
+        let max = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        (prefix <= max).then_some(Self { addr, prefix })
+    }
+
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Whether `addr` lies within this network.
+    ///
+    /// IPv4-mapped IPv6 addresses, as reported by dual-stack listeners, match
+    /// the IPv4 networks they map to.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => prefix_matches(
+                u32::from(net).into(),
+                u32::from(addr).into(),
+                32,
+                self.prefix,
+            ),
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                prefix_matches(net.into(), addr.into(), 128, self.prefix)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(net: u128, addr: u128, bits: u8, prefix: u8) -> bool {
+    let shift = u32::from(bits - prefix);
+    net.checked_shr(shift).unwrap_or(0) == addr.checked_shr(shift).unwrap_or(0)
+}
+
+impl From<IpAddr> for Cidr {
+    fn from(addr: IpAddr) -> Self {
+        let prefix = if addr.is_ipv4() { 32 } else { 128 };
+        Self { addr, prefix }
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// Error returned when parsing a [`Cidr`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCidrError(String);
+
+impl fmt::Display for ParseCidrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid CIDR network: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseCidrError {}
+
+impl FromStr for Cidr {
+    type Err = ParseCidrError;
+
+    /// Parses `addr/prefix`; a bare address is treated as a single host.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseCidrError(s.to_owned());
+        match s.split_once('/') {
+            Some((addr, prefix)) => {
+                let addr = addr.parse().map_err(|_| err())?;
+                let prefix = prefix.parse().map_err(|_| err())?;
+                Cidr::new(addr, prefix).ok_or_else(err)
+            }
+            None => s.parse::<IpAddr>().map(Cidr::from).map_err(|_| err()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Rules {
+    allow: Vec<Cidr>,
+    deny: Vec<Cidr>,
+}
+
+/// A runtime-updatable allow/deny list of source networks.
+///
+/// An address is admitted if it matches no deny rule and, when any allow rules
+/// are present, matches at least one of them. Deny rules therefore take
+/// precedence, and an empty filter admits everyone.
+///
+/// Clones share the same rule set, so a handle kept by the application can
+/// update the rules consulted by a running listener.
+#[derive(Debug, Clone, Default)]
+pub struct IpFilter {
+    rules: Arc<RwLock<Rules>>,
+}
+
+impl IpFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a network to the allow list.
+    pub fn allow(&self, net: Cidr) {
+        self.rules.write().unwrap().allow.push(net);
+    }
+
+    /// Adds a network to the deny list.
+    pub fn deny(&self, net: Cidr) {
+        self.rules.write().unwrap().deny.push(net);
+    }
+
+    /// Atomically replaces both lists.
+    pub fn set_rules(&self, allow: Vec<Cidr>, deny: Vec<Cidr>) {
+        *self.rules.write().unwrap() = Rules { allow, deny };
+    }
+
+    /// Removes every rule, admitting all addresses.
+    pub fn clear(&self) {
+        self.set_rules(Vec::new(), Vec::new());
+    }
+
+    /// Whether a connection from `addr` should be admitted.
+    pub fn is_allowed(&self, addr: IpAddr) -> bool {
+        let rules = self.rules.read().unwrap();
+        if rules.deny.iter().any(|net| net.contains(addr)) {
+            return false;
+        }
+        rules.allow.is_empty() || rules.allow.iter().any(|net| net.contains(addr))
+    }
+}
+
+/// A token bucket refill rate and capacity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rate {
+    /// Handshakes admitted per second once the burst is spent.
+    pub per_second: f64,
+    /// Handshakes admitted back to back from a full bucket.
+    pub burst: u32,
+}
+
+impl Rate {
+    /// # Panics
+    ///
+    /// Panics if `per_second` is negative or not finite.
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        let rate = Self { per_second, burst };
+        rate.validate();
+        rate
+    }
+
+    fn validate(&self) {
+        assert!(
+            self.per_second.is_finite() && self.per_second >= 0.0,
+            "handshake rate must be finite and non-negative, got {}",
+            self.per_second
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn full(rate: Rate, now: Instant) -> Self {
+        Self {
+            tokens: rate.burst as f64,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, rate: Rate, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate.per_second).min(rate.burst as f64);
+        self.updated = now;
+    }
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    global: TokenBucket,
+    per_ip: HashMap<IpAddr, TokenBucket>,
+}
+
+/// Token bucket rate limiting of handshakes, per source address and globally.
+///
+/// An attempt consumes one token from both the global bucket and the bucket
+/// of its source address; if either is empty the attempt is refused with
+/// [`RejectReason::Resource`], without consuming from the other.
+#[derive(Debug)]
+pub struct HandshakeLimiter {
+    per_ip: Rate,
+    global: Rate,
+    state: Mutex<LimiterState>,
+}
+
+impl HandshakeLimiter {
+    /// The most source addresses tracked at once. Past this, the least
+    /// recently seen half is forgotten.
+    const MAX_SOURCES: usize = 4096;
+
+    /// # Panics
+    ///
+    /// Panics if either rate is negative or not finite.
+    pub fn new(per_ip: Rate, global: Rate) -> Self {
+        per_ip.validate();
+        global.validate();
+        let now = Instant::now();
+        Self {
+            per_ip,
+            global,
+            state: Mutex::new(LimiterState {
+                global: TokenBucket::full(global, now),
+                per_ip: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Checks whether a handshake from `addr` may proceed now.
+    pub fn check(&self, addr: IpAddr) -> Result<(), RejectReason> {
+        self.check_at(addr, Instant::now())
+    }
+
+    /// Checks whether a handshake from `addr` may proceed at `now`.
+    pub fn check_at(&self, addr: IpAddr, now: Instant) -> Result<(), RejectReason> {
+        let mut state = self.state.lock().unwrap();
+        let LimiterState { global, per_ip } = &mut *state;
+
+        // Refuse before tracking the source, so a flood from spoofed
+        // addresses cannot grow the table faster than the global rate.
+        global.refill(self.global, now);
+        if global.tokens < 1.0 {
+            return Err(RejectReason::Resource);
+        }
+
+        let addr = addr.to_canonical();
+        if per_ip.len() >= Self::MAX_SOURCES && !per_ip.contains_key(&addr) {
+            forget_oldest_half(per_ip);
+        }
+        let source = per_ip
+            .entry(addr)
+            .or_insert_with(|| TokenBucket::full(self.per_ip, now));
+        source.refill(self.per_ip, now);
+        if source.tokens < 1.0 {
+            return Err(RejectReason::Resource);
+        }
+        global.tokens -= 1.0;
+        source.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+/// Drops the buckets of the least recently seen half of `per_ip`. Running
+/// only once the table is full keeps the cost amortised constant per
+/// source.
+fn forget_oldest_half(per_ip: &mut HashMap<IpAddr, TokenBucket>) {
+    let mut seen: Vec<Instant> = per_ip.values().map(|bucket| bucket.updated).collect();
+    let middle = seen.len() / 2;
+    let (_, &mut cutoff, _) = seen.select_nth_unstable(middle);
+    per_ip.retain(|_, bucket| bucket.updated > cutoff);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_matches_networks() {
+        let net: Cidr = "192.168.0.0/16".parse().unwrap();
+        assert!(net.contains(ip("192.168.4.2")));
+        assert!(net.contains(ip("::ffff:192.168.4.2")));
+        assert!(!net.contains(ip("192.169.0.1")));
+
+        let any: Cidr = "::/0".parse().unwrap();
+        assert!(any.contains(ip("2001:db8::1")));
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+    }
+
+    #[test]
+    fn deny_takes_precedence_over_allow() {
+        let filter = IpFilter::new();
+        assert!(filter.is_allowed(ip("203.0.113.7")));
+
+        filter.allow("10.0.0.0/8".parse().unwrap());
+        filter.deny("10.0.0.13".parse().unwrap());
+        assert!(filter.is_allowed(ip("10.1.2.3")));
+        assert!(!filter.is_allowed(ip("10.0.0.13")));
+        assert!(!filter.is_allowed(ip("203.0.113.7")));
+
+        filter.clone().clear();
+        assert!(filter.is_allowed(ip("10.0.0.13")));
+    }
+
+    #[test]
+    fn limits_per_source_and_globally() {
+        use std::time::Duration;
+
+        let limiter = HandshakeLimiter::new(Rate::new(1.0, 2), Rate::new(10.0, 3));
+        let now = Instant::now();
+        let a = ip("198.51.100.1");
+        let b = ip("198.51.100.2");
+
+        assert_eq!(limiter.check_at(a, now), Ok(()));
+        assert_eq!(limiter.check_at(a, now), Ok(()));
+        assert_eq!(limiter.check_at(a, now), Err(RejectReason::Resource));
+        assert_eq!(limiter.check_at(b, now), Ok(()));
+        assert_eq!(limiter.check_at(b, now), Err(RejectReason::Resource));
+
+        let later = now + Duration::from_secs(1);
+        assert_eq!(limiter.check_at(a, later), Ok(()));
+        assert_eq!(limiter.check_at(a, later), Err(RejectReason::Resource));
+    }
+
+    #[test]
+    fn bounds_tracked_sources() {
+        let limiter = HandshakeLimiter::new(Rate::new(1.0, 1), Rate::new(0.0, 10_000));
+        let now = Instant::now();
+        let source = |i: u32| IpAddr::from(std::net::Ipv4Addr::from(0x0a00_0000 + i));
+        for i in 0..10_000 {
+            let at = now + std::time::Duration::from_micros(u64::from(i));
+            assert_eq!(limiter.check_at(source(i), at), Ok(()));
+        }
+        let tracked = limiter.state.lock().unwrap().per_ip.len();
+        assert!(tracked <= HandshakeLimiter::MAX_SOURCES);
+
+        // With the global bucket spent, new sources are not tracked at all.
+        assert!(limiter.check_at(source(20_000), now).is_err());
+        assert!(!limiter
+            .state
+            .lock()
+            .unwrap()
+            .per_ip
+            .contains_key(&source(20_000)));
+    }
+
+    #[test]
+    #[should_panic(expected = "handshake rate must be finite")]
+    fn rejects_nan_rates() {
+        let nan = Rate {
+            per_second: f64::NAN,
+            burst: 1,
+        };
+        HandshakeLimiter::new(Rate::new(1.0, 1), nan);
+    }
+}
@@ -0,0 +1,284 @@
+//! Cooperative shutdown signalling.
+//!
+//! A [`CancellationToken`] is shared between the code that decides to shut
+//! down and the tasks that must stop. Like the timers, it works both from
+//! plain threads and from any async runtime.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct State {
+    cancelled: bool,
+    next_id: u64,
+    callbacks: BTreeMap<u64, Callback>,
+    wakers: BTreeMap<u64, Waker>,
+}
+
+impl State {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    state: Mutex<State>,
+    condvar: Condvar,
+    /// A child's registration with its parent, removed when the child goes.
+    parent: Mutex<Option<CancelRegistration>>,
+}
+
+/// A cloneable signal that tells tasks to stop.
+///
+/// All clones observe the same cancellation. Child tokens are cancelled
+/// with their parent but can also be cancelled on their own.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a token that is cancelled when this one is. Dropping every
+    /// clone of the child removes it from this token.
+    pub fn child(&self) -> Self {
+        let child = Self::new();
+        let weak = Arc::downgrade(&child.inner);
+        let registration = self.on_cancel(move || {
+            if let Some(inner) = weak.upgrade() {
+                CancellationToken { inner }.cancel();
+            }
+        });
+        *child.inner.parent.lock().unwrap() = Some(registration);
+        child
+    }
+
+    /// Cancels the token, waking every waiter and running registered
+    /// callbacks. Cancelling again has no effect.
+    pub fn cancel(&self) {
+        let (callbacks, wakers) = {
+            let mut state = self.inner.state.lock().unwrap();
+            if state.cancelled {
+                return;
+            }
+            state.cancelled = true;
+            (
+                std::mem::take(&mut state.callbacks),
+                std::mem::take(&mut state.wakers),
+            )
+        };
+        self.inner.condvar.notify_all();
+        wakers.into_values().for_each(Waker::wake);
+        callbacks.into_values().for_each(|callback| callback());
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.state.lock().unwrap().cancelled
+    }
+
+    /// Runs `callback` once the token is cancelled, or immediately if it
+    /// already has been.
+    ///
+    /// This is how blocking work is interrupted: the callback closes the
+    /// queue or sets the flag the work is waiting on. The callback stays
+    /// registered only as long as the returned registration, so work that
+    /// finishes first does not leave it behind on a long-lived token.
+    pub fn on_cancel<F>(&self, callback: F) -> CancelRegistration
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.cancelled {
+            drop(state);
+            callback();
+            return CancelRegistration {
+                inner: Weak::new(),
+                id: 0,
+            };
+        }
+        let id = state.next_id();
+        state.callbacks.insert(id, Box::new(callback));
+        CancelRegistration {
+            inner: Arc::downgrade(&self.inner),
+            id,
+        }
+    }
+
+    /// Blocks the current thread until the token is cancelled.
+    pub fn wait(&self) {
+        let state = self.inner.state.lock().unwrap();
+        let _unused = self
+            .inner
+            .condvar
+            .wait_while(state, |state| !state.cancelled)
+            .unwrap();
+    }
+
+    /// Blocks for at most `timeout`, returning whether the token was
+    /// cancelled.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let state = self.inner.state.lock().unwrap();
+        let (state, _) = self
+            .inner
+            .condvar
+            .wait_timeout_while(state, timeout, |state| !state.cancelled)
+            .unwrap();
+        state.cancelled
+    }
+
+    /// Completes once the token is cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            token: self.clone(),
+            id: None,
+        }
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Keeps a callback registered with [`CancellationToken::on_cancel`].
+///
+/// Dropping it removes the callback if it has not run yet.
+#[derive(Debug)]
+#[must_use = "dropping the registration removes the callback"]
+pub struct CancelRegistration {
+    inner: Weak<Inner>,
+    id: u64,
+}
+
+impl Drop for CancelRegistration {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.upgrade() {
+            let callback = inner.state.lock().unwrap().callbacks.remove(&self.id);
+            // Dropped outside the lock, as it may own tokens of its own.
+            drop(callback);
+        }
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Cancelled {
+    token: CancellationToken,
+    /// The key of this future's waker, once registered.
+    id: Option<u64>,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let mut state = this.token.inner.state.lock().unwrap();
+        if state.cancelled {
+            return Poll::Ready(());
+        }
+        let id = match this.id {
+            Some(id) => id,
+            None => *this.id.insert(state.next_id()),
+        };
+        let registered = state
+            .wakers
+            .get(&id)
+            .is_some_and(|waker| waker.will_wake(cx.waker()));
+        if !registered {
+            state.wakers.insert(id, cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.token.inner.state.lock().unwrap().wakers.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::tests::block_on;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    #[test]
+    fn cancels_children_and_runs_callbacks() {
+        let parent = CancellationToken::new();
+        let child = parent.child();
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&ran);
+        let _registration = child.on_cancel(move || flag.store(true, Ordering::SeqCst));
+
+        assert!(!child.wait_timeout(Duration::from_millis(1)));
+        child.cancel();
+        assert!(ran.load(Ordering::SeqCst));
+        assert!(!parent.is_cancelled());
+
+        let other = parent.child();
+        parent.cancel();
+        assert!(other.is_cancelled());
+    }
+
+    #[test]
+    fn wakes_futures_and_threads() {
+        let token = CancellationToken::new();
+        let waiter = {
+            let token = token.clone();
+            thread::spawn(move || token.wait())
+        };
+        let canceller = {
+            let token = token.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(10));
+                token.cancel();
+            })
+        };
+        block_on(token.cancelled());
+        waiter.join().unwrap();
+        canceller.join().unwrap();
+    }
+
+    #[test]
+    fn dropped_registrations_are_removed() {
+        let token = CancellationToken::new();
+        let registration = token.on_cancel(|| panic!("deregistered callback ran"));
+        let child = token.child();
+        let mut cancelled = Box::pin(token.cancelled());
+        let waker = std::task::Waker::noop();
+        assert!(cancelled
+            .as_mut()
+            .poll(&mut Context::from_waker(waker))
+            .is_pending());
+
+        let pending = |token: &CancellationToken| {
+            let state = token.inner.state.lock().unwrap();
+            (state.callbacks.len(), state.wakers.len())
+        };
+        assert_eq!(pending(&token), (2, 1));
+        drop((registration, child, cancelled));
+        assert_eq!(pending(&token), (0, 0));
+        token.cancel();
+    }
+}
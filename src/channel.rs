@@ -0,0 +1,273 @@
+//! Channel adapters that pump a transport from background threads.
+
+use std::io;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+
+use crate::cancel::{CancelRegistration, CancellationToken};
+use crate::copy::LIVE_MAX_PAYLOAD_SIZE;
+use crate::queue::{BoundedQueue, Closed, OverflowPolicy, Priority};
+use crate::transport::SrtTransport;
+
+/// Queue sizing and overflow behaviour for [`into_channels`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelConfig {
+    /// Messages buffered in each direction.
+    pub capacity: usize,
+    /// Applied when the application sends faster than the link drains.
+    pub send_overflow: OverflowPolicy,
+    /// Applied when messages arrive faster than the application receives.
+    pub recv_overflow: OverflowPolicy,
+    /// Largest message the receive pump accepts.
+    pub max_message_size: usize,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            send_overflow: OverflowPolicy::Block,
+            recv_overflow: OverflowPolicy::Block,
+            max_message_size: LIVE_MAX_PAYLOAD_SIZE,
+        }
+    }
+}
+
+/// The sending half returned by [`into_channels`] and [`queued_sender`].
+///
+/// Dropping it stops the send pump once queued messages have been sent.
+#[derive(Debug)]
+pub struct ChannelSender {
+    queue: Arc<BoundedQueue<Vec<u8>>>,
+    cancel: Mutex<Option<CancelRegistration>>,
+}
+
+impl ChannelSender {
+    /// Queues `message` for sending. Fails once the send pump has stopped
+    /// because the transport reported an error.
+    pub fn send(&self, message: Vec<u8>) -> Result<(), Closed<Vec<u8>>> {
+        self.queue.push(message)
+    }
+
+    /// Queues `message` with the given priority; [`Priority::High`]
+    /// messages are sent ahead of any queued normal ones.
+    pub fn send_with_priority(
+        &self,
+        message: Vec<u8>,
+        priority: Priority,
+    ) -> Result<(), Closed<Vec<u8>>> {
+        self.queue.push_with_priority(message, priority)
+    }
+
+    /// Messages discarded by the send overflow policy so far.
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped()
+    }
+
+    /// Closes the queue once `token` is cancelled. Messages already queued
+    /// are still sent, then the send pump stops. Replaces any token given
+    /// before.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        *self.cancel.lock().unwrap() = Some(close_on(Arc::downgrade(&self.queue), token));
+    }
+}
+
+impl Drop for ChannelSender {
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+/// The receiving half returned by [`into_channels`].
+#[derive(Debug)]
+pub struct ChannelReceiver {
+    queue: Arc<BoundedQueue<Vec<u8>>>,
+    cancel: Mutex<Option<CancelRegistration>>,
+}
+
+impl ChannelReceiver {
+    /// Waits for the next message. Returns `None` once the peer has closed
+    /// the connection or the transport failed, and queued messages are
+    /// drained.
+    pub fn recv(&self) -> Option<Vec<u8>> {
+        self.queue.pop()
+    }
+
+    /// Returns the next message if one is already queued.
+    pub fn try_recv(&self) -> Option<Vec<u8>> {
+        self.queue.try_pop()
+    }
+
+    /// Messages discarded by the receive overflow policy so far.
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped()
+    }
+
+    /// Closes the queue once `token` is cancelled, ending iteration once
+    /// queued messages are drained and stopping the receive pump after its
+    /// current `recv` returns. Replaces any token given before.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        *self.cancel.lock().unwrap() = Some(close_on(Arc::downgrade(&self.queue), token));
+    }
+}
+
+impl Iterator for ChannelReceiver {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        self.recv()
+    }
+}
+
+impl Drop for ChannelReceiver {
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+/// Splits `transport` into a sender and receiver pair, each serviced by a
+/// background thread that owns a share of the transport.
+///
+/// The receive pump notices a dropped [`ChannelReceiver`] only after its
+/// current `recv` returns, so give the transport a receive timeout if it
+/// must shut down promptly on an idle link.
+pub fn into_channels<T>(transport: T, config: ChannelConfig) -> (ChannelSender, ChannelReceiver)
+where
+    T: SrtTransport + Send + Sync + 'static,
+{
+    let transport = Arc::new(transport);
+    let sender = queued_sender(
+        Arc::clone(&transport),
+        config.capacity,
+        config.send_overflow,
+    );
+    let inbound: Arc<BoundedQueue<Vec<u8>>> =
+        Arc::new(BoundedQueue::new(config.capacity, config.recv_overflow));
+
+    {
+        let inbound = Arc::clone(&inbound);
+        let size = config.max_message_size;
+        thread::spawn(move || {
+            let mut buf = vec![0; size];
+            while !inbound.is_closed() {
+                match transport.recv(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        if inbound.push(buf[..n].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) if is_transient(&e) => {}
+                    Err(_) => break,
+                }
+            }
+            inbound.close();
+        });
+    }
+
+    let receiver = ChannelReceiver {
+        queue: inbound,
+        cancel: Mutex::new(None),
+    };
+    (sender, receiver)
+}
+
+/// Wraps the sending side of `transport` in a bounded queue serviced by a
+/// background thread, leaving the application free to receive on it
+/// directly.
+///
+/// In live mode, [`OverflowPolicy::DropOldest`] makes a stalled link shed
+/// the stalest messages rather than letting the backlog grow without bound
+/// or blocking the producer.
+pub fn queued_sender<T>(
+    transport: Arc<T>,
+    capacity: usize,
+    overflow: OverflowPolicy,
+) -> ChannelSender
+where
+    T: SrtTransport + Send + Sync + 'static,
+{
+    let queue: Arc<BoundedQueue<Vec<u8>>> = Arc::new(BoundedQueue::new(capacity, overflow));
+    {
+        let queue = Arc::clone(&queue);
+        thread::spawn(move || {
+            while let Some(message) = queue.pop() {
+                if transport.send(&message).is_err() {
+                    break;
+                }
+            }
+            queue.close();
+        });
+    }
+    ChannelSender {
+        queue,
+        cancel: Mutex::new(None),
+    }
+}
+
+fn close_on(queue: Weak<BoundedQueue<Vec<u8>>>, token: &CancellationToken) -> CancelRegistration {
+    token.on_cancel(move || {
+        if let Some(queue) = queue.upgrade() {
+            queue.close();
+        }
+    })
+}
+
+fn is_transient(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockConfig, MockTransport};
+
+    #[test]
+    fn pumps_messages_both_ways() {
+        let (local, peer) = MockTransport::pair(MockConfig::default());
+
+        let (tx, rx) = into_channels(local, ChannelConfig::default());
+        tx.send(b"hello".to_vec()).unwrap();
+        let mut buf = [0; 16];
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"hello");
+
+        peer.send(b"world").unwrap();
+        peer.send(b"").unwrap();
+        assert_eq!(rx.recv().as_deref(), Some(&b"world"[..]));
+        assert_eq!(rx.recv(), None);
+    }
+
+    #[test]
+    fn queued_sender_shares_transport() {
+        let (local, peer) = MockTransport::pair(MockConfig::default());
+        let local = Arc::new(local);
+
+        let tx = queued_sender(Arc::clone(&local), 8, OverflowPolicy::DropOldest);
+        tx.send(b"frame".to_vec()).unwrap();
+        let mut buf = [0; 16];
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"frame");
+
+        peer.send(b"reply").unwrap();
+        let n = local.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"reply");
+        assert_eq!(tx.dropped(), 0);
+    }
+
+    #[test]
+    fn cancellation_closes_channels() {
+        let (local, _peer) = MockTransport::pair(MockConfig::default());
+
+        let token = CancellationToken::new();
+        let (tx, rx) = into_channels(local, ChannelConfig::default());
+        tx.cancel_on(&token);
+        rx.cancel_on(&token);
+        token.cancel();
+        assert_eq!(rx.recv(), None);
+        assert!(tx.send(b"late".to_vec()).is_err());
+    }
+}
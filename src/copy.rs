@@ -0,0 +1,220 @@
+//! Message-preserving copies between transports.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::cancel::CancellationToken;
+use crate::transport::SrtTransport;
+
+/// The largest payload SRT carries in a single live-mode packet.
+pub const LIVE_MAX_PAYLOAD_SIZE: usize = 1456;
+
+/// How long an idle direction waits before checking whether the other has
+/// finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// The most messages read before forwarding them.
+const BATCH: usize = 16;
+
+/// Forwards messages from `a` to `b` and from `b` to `a` until either
+/// reaches end of stream or fails, returning the number of bytes copied in
+/// each direction.
+///
+/// Every received message is sent on as exactly one message, so boundaries
+/// survive the hop. Each direction runs on its own scoped thread with
+/// buffers of [`LIVE_MAX_PAYLOAD_SIZE`] bytes, reading whatever messages
+/// have already arrived, up to a batch of 16, before forwarding them. A
+/// message larger than its buffer fails the copy with
+/// [`io::ErrorKind::InvalidData`] rather than being truncated; use
+/// [`copy_bidirectional_with_sizes`] for file-mode links carrying larger
+/// messages.
+///
+/// Once one direction finishes, the other stops as soon as it has forwarded
+/// what had already arrived, and an error from either is returned.
+pub fn copy_bidirectional<A, B>(a: &A, b: &B) -> io::Result<(u64, u64)>
+where
+    A: SrtTransport + Sync,
+    B: SrtTransport + Sync,
+{
+    copy_bidirectional_with_sizes(a, b, LIVE_MAX_PAYLOAD_SIZE, LIVE_MAX_PAYLOAD_SIZE)
+}
+
+/// Like [`copy_bidirectional`], but also stops both directions once `token`
+/// is cancelled, returning the bytes copied until then.
+pub fn copy_bidirectional_until<A, B>(
+    a: &A,
+    b: &B,
+    token: &CancellationToken,
+) -> io::Result<(u64, u64)>
+where
+    A: SrtTransport + Sync,
+    B: SrtTransport + Sync,
+{
+    copy_both_ways(
+        a,
+        b,
+        LIVE_MAX_PAYLOAD_SIZE,
+        LIVE_MAX_PAYLOAD_SIZE,
+        Some(token),
+    )
+}
+
+/// Like [`copy_bidirectional`], with explicit buffer sizes for the `a` to `b`
+/// and `b` to `a` directions.
+pub fn copy_bidirectional_with_sizes<A, B>(
+    a: &A,
+    b: &B,
+    a_to_b_size: usize,
+    b_to_a_size: usize,
+) -> io::Result<(u64, u64)>
+where
+    A: SrtTransport + Sync,
+    B: SrtTransport + Sync,
+{
+    copy_both_ways(a, b, a_to_b_size, b_to_a_size, None)
+}
+
+fn copy_both_ways<A, B>(
+    a: &A,
+    b: &B,
+    a_to_b_size: usize,
+    b_to_a_size: usize,
+    token: Option<&CancellationToken>,
+) -> io::Result<(u64, u64)>
+where
+    A: SrtTransport + Sync,
+    B: SrtTransport + Sync,
+{
+    let stop = AtomicBool::new(false);
+    let stopped = || stop.load(Ordering::Relaxed) || token.is_some_and(|t| t.is_cancelled());
+    let copy = |from: &dyn SrtTransport, to: &dyn SrtTransport, size| {
+        let result = copy_messages(from, to, size, &stopped);
+        stop.store(true, Ordering::Relaxed);
+        result
+    };
+    thread::scope(|scope| {
+        let b_to_a = scope.spawn(|| copy(b, a, b_to_a_size));
+        let a_to_b = copy(a, b, a_to_b_size);
+        let b_to_a = b_to_a.join().expect("copy thread panicked");
+        Ok((a_to_b?, b_to_a?))
+    })
+}
+
+fn copy_messages(
+    from: &dyn SrtTransport,
+    to: &dyn SrtTransport,
+    size: usize,
+    stopped: &dyn Fn() -> bool,
+) -> io::Result<u64> {
+    // A spare byte per slot shows when a message did not fit.
+    let slot = size + 1;
+    let mut buf = vec![0; slot * BATCH];
+    let mut lens = Vec::with_capacity(BATCH);
+    let mut copied = 0;
+    loop {
+        let mut end = None;
+        lens.clear();
+        while lens.len() < BATCH {
+            // Block for the first message only, then take what has arrived.
+            let timeout = if lens.is_empty() {
+                POLL_INTERVAL
+            } else {
+                Duration::ZERO
+            };
+            match from.recv_timeout(&mut buf[lens.len() * slot..][..slot], Some(timeout)) {
+                Ok(0) => end = Some(Ok(())),
+                Ok(n) if n > size => {
+                    end = Some(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("message larger than the {size} byte copy buffer"),
+                    )))
+                }
+                Ok(n) => {
+                    lens.push(n);
+                    continue;
+                }
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                    if !lens.is_empty() {
+                        break;
+                    }
+                    if stopped() {
+                        end = Some(Ok(()));
+                    } else {
+                        continue;
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => end = Some(Err(e)),
+            }
+            break;
+        }
+        for (i, &n) in lens.iter().enumerate() {
+            to.send(&buf[i * slot..][..n])?;
+            copied += n as u64;
+        }
+        if let Some(end) = end {
+            return end.map(|()| copied);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockConfig, MockTransport};
+
+    fn pair() -> (MockTransport, MockTransport) {
+        MockTransport::pair(MockConfig::default())
+    }
+
+    #[test]
+    fn forwards_messages_both_ways() {
+        let (left, proxy_left) = pair();
+        let (proxy_right, right) = pair();
+
+        left.send(b"ping").unwrap();
+        left.send(b"").unwrap();
+        right.send(b"pong!").unwrap();
+        right.send(b"").unwrap();
+
+        let copied = copy_bidirectional(&proxy_left, &proxy_right).unwrap();
+        assert_eq!(copied, (4, 5));
+
+        let mut buf = [0; 16];
+        let n = right.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        let n = left.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"pong!");
+    }
+
+    #[test]
+    fn stops_both_ways_when_one_fails() {
+        let (left, proxy_left) = pair();
+        // `right` stays open, so the other direction must be stopped.
+        let (proxy_right, _right) = pair();
+
+        left.send(&[0; 32]).unwrap();
+        // The mock, like libsrt, refuses to truncate the oversized message.
+        let err = copy_bidirectional_with_sizes(&proxy_left, &proxy_right, 16, 16).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let (left, proxy_left) = pair();
+        let (proxy_right, right) = pair();
+        let token = CancellationToken::new();
+        left.send(b"before").unwrap();
+
+        let copied = thread::scope(|scope| {
+            let copy = scope.spawn(|| copy_bidirectional_until(&proxy_left, &proxy_right, &token));
+            let mut buf = [0; 16];
+            let n = right.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..n], b"before");
+            token.cancel();
+            copy.join().unwrap()
+        });
+        assert_eq!(copied.unwrap(), (6, 0));
+    }
+}
@@ -0,0 +1,131 @@
+//! Process-wide message and error counters.
+//!
+//! The crate's transports update these on every send and receive with
+//! relaxed atomic increments, so reading them costs no locking and keeping
+//! them costs next to nothing on the hot path. They suit health checks
+//! that only need to see traffic moving.
+
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The process-wide counters, read through [`counters`].
+#[derive(Debug)]
+pub struct Counters {
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    timeouts: AtomicU64,
+    connection_errors: AtomicU64,
+    other_errors: AtomicU64,
+}
+
+/// A point-in-time copy of the [`Counters`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CounterSnapshot {
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Sends and receives that timed out or would have blocked.
+    pub timeouts: u64,
+    /// Sends and receives that failed because the connection was refused,
+    /// reset, aborted or is not connected.
+    pub connection_errors: u64,
+    /// Any other failed sends and receives.
+    pub other_errors: u64,
+}
+
+static COUNTERS: Counters = Counters::new();
+
+/// The process-wide counters.
+pub fn counters() -> &'static Counters {
+    &COUNTERS
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            messages_sent: AtomicU64::new(0),
+            messages_received: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            connection_errors: AtomicU64::new(0),
+            other_errors: AtomicU64::new(0),
+        }
+    }
+
+    /// Reads every counter. Each is read atomically, but the snapshot as a
+    /// whole is not, so counters updated concurrently may be slightly out
+    /// of step with one another.
+    pub fn snapshot(&self) -> CounterSnapshot {
+        CounterSnapshot {
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            connection_errors: self.connection_errors.load(Ordering::Relaxed),
+            other_errors: self.other_errors.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn record_send(&self, result: &io::Result<usize>) {
+        match result {
+            Ok(n) => {
+                self.messages_sent.fetch_add(1, Ordering::Relaxed);
+                self.bytes_sent.fetch_add(*n as u64, Ordering::Relaxed);
+            }
+            Err(e) => self.record_error(e),
+        }
+    }
+
+    /// Records a receive; `Ok(0)` marks the peer closing, not a message.
+    pub(crate) fn record_recv(&self, result: &io::Result<usize>) {
+        match result {
+            Ok(0) => {}
+            Ok(n) => {
+                self.messages_received.fetch_add(1, Ordering::Relaxed);
+                self.bytes_received.fetch_add(*n as u64, Ordering::Relaxed);
+            }
+            Err(e) => self.record_error(e),
+        }
+    }
+
+    fn record_error(&self, err: &io::Error) {
+        use io::ErrorKind::*;
+        let counter = match err.kind() {
+            TimedOut | WouldBlock => &self.timeouts,
+            ConnectionRefused | ConnectionReset | ConnectionAborted | NotConnected | BrokenPipe => {
+                &self.connection_errors
+            }
+            _ => &self.other_errors,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_results() {
+        // The global counters are shared with other tests, so use a local set.
+        let counters = Counters::new();
+        counters.record_send(&Ok(100));
+        counters.record_recv(&Ok(40));
+        counters.record_recv(&Ok(0));
+        counters.record_recv(&Err(io::ErrorKind::TimedOut.into()));
+        counters.record_send(&Err(io::ErrorKind::ConnectionReset.into()));
+
+        let snapshot = counters.snapshot();
+        assert_eq!(snapshot.messages_sent, 1);
+        assert_eq!(snapshot.bytes_sent, 100);
+        assert_eq!(snapshot.messages_received, 1);
+        assert_eq!(snapshot.timeouts, 1);
+        assert_eq!(snapshot.connection_errors, 1);
+        assert_eq!(snapshot.other_errors, 0);
+    }
+}
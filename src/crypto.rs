@@ -0,0 +1,345 @@
+//! Encryption key material state and passphrases.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use crate::reject::Reject;
+
+/// The state of a socket's key material, mirroring libsrt's `SRT_KM_STATE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KmState {
+    /// No encryption is configured.
+    Unsecured,
+    /// Key material exchange is in progress.
+    Securing,
+    /// Keys are in place and traffic is encrypted.
+    Secured,
+    /// The peer is encrypting but no passphrase is configured locally.
+    NoSecret,
+    /// The passphrases on both sides differ.
+    BadSecret,
+    /// The crypto modes on both sides differ.
+    BadCryptoMode,
+}
+
+impl KmState {
+    /// Maps libsrt's numeric `SRT_KM_S_*` value.
+    pub fn from_raw(raw: i32) -> Option<Self> {
+        Some(match raw {
+            0 => KmState::Unsecured,
+            1 => KmState::Securing,
+            2 => KmState::Secured,
+            3 => KmState::NoSecret,
+            4 => KmState::BadSecret,
+            5 => KmState::BadCryptoMode,
+            _ => return None,
+        })
+    }
+}
+
+/// The cipher mode, mirroring libsrt's `SRTO_CRYPTOMODE` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CryptoMode {
+    /// Not yet negotiated; the caller adopts the listener's mode.
+    Auto,
+    AesCtr,
+    AesGcm,
+}
+
+impl CryptoMode {
+    pub fn from_raw(raw: i32) -> Option<Self> {
+        Some(match raw {
+            0 => CryptoMode::Auto,
+            1 => CryptoMode::AesCtr,
+            2 => CryptoMode::AesGcm,
+            _ => return None,
+        })
+    }
+}
+
+/// Whether, and how, a connection is encrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncryptionStatus {
+    /// The state of the keys used for incoming data (`SRTO_RCVKMSTATE`).
+    pub state: KmState,
+    /// The negotiated key length in bytes (`SRTO_PBKEYLEN`), if encrypted.
+    pub key_len: Option<u8>,
+    /// The negotiated cipher mode, if encrypted and reported by libsrt.
+    /// `SRTO_CRYPTOMODE` requires libsrt 1.5.2.
+    pub mode: Option<CryptoMode>,
+}
+
+impl EncryptionStatus {
+    /// Builds the status from the raw values of `SRTO_RCVKMSTATE`,
+    /// `SRTO_PBKEYLEN` and, where available, `SRTO_CRYPTOMODE`.
+    pub fn from_raw(km_state: i32, pbkeylen: i32, crypto_mode: Option<i32>) -> Option<Self> {
+        let state = KmState::from_raw(km_state)?;
+        let secured = state == KmState::Secured;
+        let key_len = u8::try_from(pbkeylen)
+            .ok()
+            .filter(|len| secured && matches!(len, 16 | 24 | 32));
+        let mode = crypto_mode
+            .and_then(CryptoMode::from_raw)
+            .filter(|_| secured);
+        Some(Self {
+            state,
+            key_len,
+            mode,
+        })
+    }
+
+    /// Whether traffic on the connection is encrypted, for policies that
+    /// refuse to forward unencrypted streams.
+    pub fn is_encrypted(&self) -> bool {
+        self.state == KmState::Secured
+    }
+}
+
+/// Which side's key material a [`KmEvent`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KmDirection {
+    /// Keys used to encrypt outgoing data (`SRTO_SNDKMSTATE`).
+    Send,
+    /// Keys used to decrypt incoming data (`SRTO_RCVKMSTATE`).
+    Receive,
+}
+
+/// A change in key material state observed by a [`KmWatcher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KmEvent {
+    pub direction: KmDirection,
+    pub from: KmState,
+    pub to: KmState,
+    pub at: Instant,
+}
+
+/// Tracks the send and receive key material states of a socket, reporting
+/// each transition and counting how many have occurred.
+///
+/// Poll `SRTO_SNDKMSTATE`/`SRTO_RCVKMSTATE` periodically and feed them in
+/// here; a failed exchange shows up as a transition out of
+/// [`KmState::Secured`].
+///
+/// Successful key refreshes are not observable: libsrt neither reports them
+/// nor exposes the active key index, and the state stays
+/// [`KmState::Secured`] across a refresh. Transitions therefore cannot
+/// confirm that keys rotate at the configured `SRTO_KMREFRESHRATE`.
+pub struct KmWatcher {
+    send: Option<KmState>,
+    recv: Option<KmState>,
+    transitions: u64,
+    on_event: Box<dyn FnMut(KmEvent) + Send>,
+}
+
+impl KmWatcher {
+    pub fn new<F>(on_event: F) -> Self
+    where
+        F: FnMut(KmEvent) + Send + 'static,
+    {
+        Self {
+            send: None,
+            recv: None,
+            transitions: 0,
+            on_event: Box::new(on_event),
+        }
+    }
+
+    /// Records the states observed at the current instant.
+    pub fn sample(&mut self, send: KmState, recv: KmState) {
+        self.sample_at(Instant::now(), send, recv);
+    }
+
+    /// Records the states observed at `at`.
+    pub fn sample_at(&mut self, at: Instant, send: KmState, recv: KmState) {
+        let updates = [
+            (KmDirection::Send, self.send.replace(send), send),
+            (KmDirection::Receive, self.recv.replace(recv), recv),
+        ];
+        for (direction, from, to) in updates {
+            if let Some(from) = from.filter(|&from| from != to) {
+                self.transitions += 1;
+                (self.on_event)(KmEvent {
+                    direction,
+                    from,
+                    to,
+                    at,
+                });
+            }
+        }
+    }
+
+    /// The number of transitions observed so far, in either direction.
+    pub fn transitions(&self) -> u64 {
+        self.transitions
+    }
+
+    pub fn send_state(&self) -> Option<KmState> {
+        self.send
+    }
+
+    pub fn recv_state(&self) -> Option<KmState> {
+        self.recv
+    }
+}
+
+/// A passphrase of valid length for `SRTO_PASSPHRASE`.
+///
+/// The contents are redacted from `Debug` output.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Passphrase(String);
+
+impl Passphrase {
+    /// The shortest passphrase libsrt accepts, in bytes.
+    pub const MIN_LEN: usize = 10;
+    /// The longest passphrase libsrt accepts, in bytes.
+    pub const MAX_LEN: usize = 79;
+
+    pub fn new(passphrase: impl Into<String>) -> Result<Self, InvalidPassphrase> {
+        let passphrase = passphrase.into();
+        if (Self::MIN_LEN..=Self::MAX_LEN).contains(&passphrase.len()) {
+            Ok(Self(passphrase))
+        } else {
+            Err(InvalidPassphrase {
+                len: passphrase.len(),
+            })
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(..)")
+    }
+}
+
+/// Error returned when a passphrase is too short or too long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidPassphrase {
+    pub len: usize,
+}
+
+impl fmt::Display for InvalidPassphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "passphrase is {} bytes, expected {} to {}",
+            self.len,
+            Passphrase::MIN_LEN,
+            Passphrase::MAX_LEN
+        )
+    }
+}
+
+impl std::error::Error for InvalidPassphrase {}
+
+/// Details of a connection whose passphrase is being looked up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PassphraseRequest<'a> {
+    /// The StreamID announced by the caller, or configured for it.
+    pub stream_id: Option<&'a str>,
+    /// The remote address of the connection.
+    pub peer: SocketAddr,
+}
+
+/// Supplies passphrases lazily, as each connection is made or accepted.
+///
+/// This lets secrets be fetched from a secrets manager, keyed by StreamID or
+/// peer, rather than being fixed when the socket is configured. Returning
+/// `Ok(None)` leaves the connection unencrypted, which a listener with
+/// `SRTO_ENFORCEDENCRYPTION` will then reject. Returning an error rejects
+/// the connection outright, for when the passphrase cannot be determined.
+pub trait PassphraseProvider: Send + Sync {
+    fn passphrase(&self, request: &PassphraseRequest<'_>) -> Result<Option<Passphrase>, Reject>;
+}
+
+impl<F> PassphraseProvider for F
+where
+    F: Fn(&PassphraseRequest<'_>) -> Result<Option<Passphrase>, Reject> + Send + Sync,
+{
+    fn passphrase(&self, request: &PassphraseRequest<'_>) -> Result<Option<Passphrase>, Reject> {
+        self(request)
+    }
+}
+
+impl PassphraseProvider for Passphrase {
+    fn passphrase(&self, _: &PassphraseRequest<'_>) -> Result<Option<Passphrase>, Reject> {
+        Ok(Some(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn reports_transitions() {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = KmWatcher::new(move |event| tx.send(event).unwrap());
+
+        watcher.sample(KmState::Securing, KmState::Securing);
+        watcher.sample(KmState::Secured, KmState::Securing);
+        watcher.sample(KmState::Secured, KmState::BadSecret);
+
+        let events: Vec<_> = rx.try_iter().map(|e| (e.direction, e.from, e.to)).collect();
+        assert_eq!(
+            events,
+            [
+                (KmDirection::Send, KmState::Securing, KmState::Secured),
+                (KmDirection::Receive, KmState::Securing, KmState::BadSecret),
+            ]
+        );
+        assert_eq!(watcher.transitions(), 2);
+    }
+
+    #[test]
+    fn decodes_encryption_status() {
+        let status = EncryptionStatus::from_raw(2, 32, Some(2)).unwrap();
+        assert!(status.is_encrypted());
+        assert_eq!(status.key_len, Some(32));
+        assert_eq!(status.mode, Some(CryptoMode::AesGcm));
+
+        let status = EncryptionStatus::from_raw(0, 16, None).unwrap();
+        assert!(!status.is_encrypted());
+        assert_eq!(status.key_len, None);
+        assert_eq!(EncryptionStatus::from_raw(9, 0, None), None);
+    }
+
+    #[test]
+    fn validates_passphrase_length() {
+        assert_eq!(
+            Passphrase::new("short").unwrap_err(),
+            InvalidPassphrase { len: 5 }
+        );
+        assert!(Passphrase::new("x".repeat(80)).is_err());
+        let passphrase = Passphrase::new("correct horse").unwrap();
+        assert_eq!(format!("{passphrase:?}"), "Passphrase(..)");
+    }
+
+    #[test]
+    fn closures_provide_passphrases() {
+        let provider = |request: &PassphraseRequest<'_>| match request.stream_id {
+            Some("tenant-a") => Ok(Passphrase::new("tenant-a-secret").ok()),
+            Some(_) => Ok(None),
+            None => Err(Reject::unauthorized()),
+        };
+        let request = |stream_id| PassphraseRequest {
+            stream_id,
+            peer: "192.0.2.1:9000".parse().unwrap(),
+        };
+        let secret = provider.passphrase(&request(Some("tenant-a"))).unwrap();
+        assert_eq!(secret.unwrap().as_str(), "tenant-a-secret");
+        assert_eq!(provider.passphrase(&request(Some("tenant-b"))), Ok(None));
+        assert_eq!(
+            provider.passphrase(&request(None)),
+            Err(Reject::unauthorized())
+        );
+    }
+}
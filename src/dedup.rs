@@ -0,0 +1,172 @@
+//! Duplicate suppression for redundant feeds.
+//!
+//! When the same stream arrives over two or more independent connections,
+//! in the manner of SMPTE 2022-7 seamless protection, a [`Deduplicator`]
+//! passes the first copy of each message and drops the rest, so a message
+//! lost on one link is filled in from another.
+//!
+//! Copies are matched on a number that increases by one per message, which
+//! lets the window be a bitmap. The source time (`srctime`) is not such a
+//! number: it has gaps of arbitrary length and differs between links
+//! unless the sender stamps each copy alike, so keying on it is left to
+//! the application.
+
+use std::collections::VecDeque;
+
+use crate::stats::{MESSAGE_LAST, SEQUENCE_SPACE};
+
+/// The modulus of RTP sequence numbers, which are 16 bits wide.
+const RTP_SPACE: u32 = 1 << 16;
+
+/// Decides which copies of a message received over redundant links to keep,
+/// keyed on a wrapping number that each link carries identically.
+///
+/// Numbers are tracked in a window behind the highest seen. A number older
+/// than the window can no longer be told apart from a duplicate and is
+/// dropped as late, so the window should cover the largest skew between the
+/// links. Messages are passed in arrival order; a receiver in TSBPD mode
+/// already delivers each link in order.
+#[derive(Debug, Clone)]
+pub struct Deduplicator {
+    /// The lowest number in use.
+    first: u32,
+    /// How many numbers are in use before they wrap back to `first`.
+    space: u32,
+    window: usize,
+    highest: Option<u32>,
+    /// Whether each number seen, from the highest backwards.
+    seen: VecDeque<bool>,
+    duplicates: u64,
+    late: u64,
+}
+
+impl Deduplicator {
+    /// Keys on packet sequence numbers (`pktseq`), remembering `window`
+    /// numbers.
+    ///
+    /// Each connection picks its own initial sequence number, so this only
+    /// suits the member links of a bonded socket group, which share one
+    /// sequence.
+    pub fn sequence(window: usize) -> Self {
+        Self::with_range(0, SEQUENCE_SPACE, window)
+    }
+
+    /// Keys on message numbers (`msgno`), remembering `window` numbers.
+    ///
+    /// The links must number their messages alike, as when the sender
+    /// writes every message to each of them from the start.
+    pub fn message_number(window: usize) -> Self {
+        Self::with_range(1, MESSAGE_LAST, window)
+    }
+
+    /// Keys on the RTP sequence numbers of RTP-over-SRT payloads,
+    /// remembering `window` numbers.
+    pub fn rtp(window: usize) -> Self {
+        Self::with_range(0, RTP_SPACE, window)
+    }
+
+    fn with_range(first: u32, space: u32, window: usize) -> Self {
+        Self {
+            first,
+            space,
+            window: window.clamp(1, space as usize / 2),
+            highest: None,
+            seen: VecDeque::new(),
+            duplicates: 0,
+            late: 0,
+        }
+    }
+
+    /// Returns whether the message numbered `number` is the first copy and
+    /// should be passed on. Numbers never sent, such as a message number of
+    /// 0, are not passed.
+    pub fn accept(&mut self, number: u32) -> bool {
+        let Some(number) = number
+            .checked_sub(self.first)
+            .filter(|&number| number < self.space)
+        else {
+            return false;
+        };
+        let Some(highest) = self.highest else {
+            self.advance(number, 1);
+            return true;
+        };
+        let ahead = (number + self.space - highest) % self.space;
+        if ahead != 0 && ahead < self.space / 2 {
+            self.advance(number, ahead as usize);
+            return true;
+        }
+        let behind = (highest + self.space - number) % self.space;
+        match self.seen.get_mut(behind as usize) {
+            Some(true) => {
+                self.duplicates += 1;
+                false
+            }
+            Some(seen) => {
+                *seen = true;
+                true
+            }
+            None => {
+                self.late += 1;
+                false
+            }
+        }
+    }
+
+    fn advance(&mut self, number: u32, ahead: usize) {
+        if ahead >= self.window {
+            self.seen.clear();
+        } else {
+            for _ in 1..ahead {
+                self.seen.push_front(false);
+            }
+        }
+        self.seen.push_front(true);
+        self.seen.truncate(self.window);
+        self.highest = Some(number);
+    }
+
+    /// Copies dropped because the number had already been passed.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// Messages dropped because their number had fallen out of the window.
+    pub fn late(&self) -> u64 {
+        self.late
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_redundant_links() {
+        let mut dedup = Deduplicator::rtp(16);
+        let a = [65534, 65535, 1, 2];
+        let b = [65534, 0, 1, 2, 3];
+        let numbers: Vec<u32> = a
+            .iter()
+            .chain(&b)
+            .copied()
+            .filter(|&number| dedup.accept(number))
+            .collect();
+        assert_eq!(numbers, [65534, 65535, 1, 2, 0, 3]);
+        assert_eq!(dedup.duplicates(), 3);
+
+        assert!(!dedup.accept(65000));
+        assert_eq!(dedup.late(), 1);
+    }
+
+    #[test]
+    fn message_numbers_wrap_to_one() {
+        let mut dedup = Deduplicator::message_number(16);
+        for msgno in [MESSAGE_LAST, 1, 2] {
+            assert!(dedup.accept(msgno));
+        }
+        assert!(!dedup.accept(MESSAGE_LAST));
+        assert!(!dedup.accept(0));
+        assert_eq!((dedup.duplicates(), dedup.late()), (1, 0));
+    }
+}
@@ -0,0 +1,106 @@
+//! A snapshot of post-connection socket flags for incident dumps.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::crypto::KmState;
+use crate::options::Version;
+
+/// The socket flags support tooling most often needs once a connection is
+/// up, read together so they can be dumped in one line.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConnectionDiagnostics {
+    /// The state of the keys used for outgoing data (`SRTO_SNDKMSTATE`).
+    pub send_km_state: KmState,
+    /// The state of the keys used for incoming data (`SRTO_RCVKMSTATE`).
+    pub recv_km_state: KmState,
+    /// The latency the peer applies to data it receives from us
+    /// (`SRTO_PEERLATENCY`).
+    pub peer_latency: Duration,
+    /// The peer's libsrt version (`SRTO_PEERVERSION`), if it reported one.
+    pub peer_version: Option<Version>,
+    /// Whether the socket may share its address (`SRTO_REUSEADDR`).
+    pub reuse_addr: bool,
+    /// The negotiated packet filter (`SRTO_PACKETFILTER`), if any.
+    pub packet_filter: Option<String>,
+}
+
+impl ConnectionDiagnostics {
+    /// Builds the snapshot from the raw values of `SRTO_SNDKMSTATE`,
+    /// `SRTO_RCVKMSTATE`, `SRTO_PEERLATENCY` in milliseconds,
+    /// `SRTO_PEERVERSION`, `SRTO_REUSEADDR` and `SRTO_PACKETFILTER`.
+    ///
+    /// Returns `None` for an unknown key material state or a negative
+    /// latency.
+    pub fn from_raw(
+        send_km_state: i32,
+        recv_km_state: i32,
+        peer_latency_ms: i32,
+        peer_version: i32,
+        reuse_addr: bool,
+        packet_filter: &str,
+    ) -> Option<Self> {
+        let peer_latency = u64::try_from(peer_latency_ms).ok()?;
+        Some(Self {
+            send_km_state: KmState::from_raw(send_km_state)?,
+            recv_km_state: KmState::from_raw(recv_km_state)?,
+            peer_latency: Duration::from_millis(peer_latency),
+            peer_version: u32::try_from(peer_version)
+                .ok()
+                .filter(|&raw| raw != 0)
+                .map(Version::from_raw),
+            reuse_addr,
+            packet_filter: (!packet_filter.is_empty()).then(|| packet_filter.to_owned()),
+        })
+    }
+}
+
+/// Formats the snapshot on one line, keyed by libsrt option names.
+impl fmt::Display for ConnectionDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sndkmstate={:?} rcvkmstate={:?} peerlatency={}ms peerversion=",
+            self.send_km_state,
+            self.recv_km_state,
+            self.peer_latency.as_millis(),
+        )?;
+        match self.peer_version {
+            Some(version) => write!(f, "{version}")?,
+            None => f.write_str("unknown")?,
+        }
+        write!(
+            f,
+            " reuseaddr={} packetfilter={}",
+            self.reuse_addr,
+            self.packet_filter.as_deref().unwrap_or("none"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_and_dumps_raw_flags() {
+        let diagnostics =
+            ConnectionDiagnostics::from_raw(2, 2, 120, 0x010503, true, "fec,cols:10").unwrap();
+        assert_eq!(diagnostics.recv_km_state, KmState::Secured);
+        assert_eq!(diagnostics.peer_version, Some(Version::new(1, 5, 3)));
+        assert_eq!(
+            diagnostics.to_string(),
+            "sndkmstate=Secured rcvkmstate=Secured peerlatency=120ms peerversion=1.5.3 \
+             reuseaddr=true packetfilter=fec,cols:10"
+        );
+
+        let diagnostics = ConnectionDiagnostics::from_raw(0, 1, 0, 0, false, "").unwrap();
+        assert_eq!(diagnostics.peer_version, None);
+        assert_eq!(diagnostics.packet_filter, None);
+        assert_eq!(ConnectionDiagnostics::from_raw(9, 0, 0, 0, false, ""), None);
+        assert_eq!(
+            ConnectionDiagnostics::from_raw(0, 0, -1, 0, false, ""),
+            None
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Spreading independent streams across several outbound links.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Clone, Copy)]
+struct Destination {
+    weight: u32,
+    health: u8,
+    streams: usize,
+}
+
+impl Destination {
+    /// The weight scaled by health; zero when the destination is down.
+    fn capacity(&self) -> u64 {
+        u64::from(self.weight) * u64::from(self.health)
+    }
+}
+
+/// A stream moved off a destination that went down.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rehome<K> {
+    pub stream: K,
+    pub from: usize,
+    /// The new destination, or `None` if every destination is down, in
+    /// which case the stream is no longer assigned.
+    pub to: Option<usize>,
+}
+
+/// Assigns streams, keyed by `K`, to destinations in proportion to each
+/// destination's weight and live health score.
+///
+/// Each new stream goes to the destination with the fewest streams relative
+/// to its weight × health, so a destination with twice the weight carries
+/// twice the streams. Health scores run from 0 (down) to 100, such as those
+/// from [`QualityWeights::score`]. Streams stay where they are while their
+/// destination is up, and are re-homed when it goes down.
+///
+/// [`QualityWeights::score`]: crate::stats::QualityWeights::score
+#[derive(Debug, Clone)]
+pub struct Distributor<K> {
+    destinations: Vec<Destination>,
+    assignments: HashMap<K, usize>,
+}
+
+impl<K: Hash + Eq + Clone> Distributor<K> {
+    pub fn new() -> Self {
+        Self {
+            destinations: Vec::new(),
+            assignments: HashMap::new(),
+        }
+    }
+
+    /// Adds a healthy destination with `weight`, returning its index.
+    pub fn add_destination(&mut self, weight: u32) -> usize {
+        self.destinations.push(Destination {
+            weight,
+            health: 100,
+            streams: 0,
+        });
+        self.destinations.len() - 1
+    }
+
+    /// Updates a destination's health score, clamped to 100. A score of 0
+    /// marks it down and re-homes its streams, returning the moves made.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `destination` is not a valid index.
+    pub fn set_health(&mut self, destination: usize, health: u8) -> Vec<Rehome<K>> {
+        self.destinations[destination].health = health.min(100);
+        if health > 0 {
+            return Vec::new();
+        }
+        let stranded: Vec<K> = self
+            .assignments
+            .iter()
+            .filter(|&(_, &dest)| dest == destination)
+            .map(|(stream, _)| stream.clone())
+            .collect();
+        stranded
+            .into_iter()
+            .map(|stream| {
+                self.release(&stream);
+                let to = self.assign(stream.clone());
+                Rehome {
+                    stream,
+                    from: destination,
+                    to,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the destination for `stream`, assigning one if it has none.
+    /// Returns `None` if every destination is down.
+    pub fn assign(&mut self, stream: K) -> Option<usize> {
+        if let Some(&dest) = self.assignments.get(&stream) {
+            return Some(dest);
+        }
+        let (dest, _) = self
+            .destinations
+            .iter()
+            .enumerate()
+            .filter(|(_, dest)| dest.capacity() > 0)
+            .min_by(|(_, a), (_, b)| {
+                // Compare (streams + 1) / capacity without dividing.
+                let a_load = (a.streams as u64 + 1) * b.capacity();
+                let b_load = (b.streams as u64 + 1) * a.capacity();
+                a_load.cmp(&b_load)
+            })?;
+        self.destinations[dest].streams += 1;
+        self.assignments.insert(stream, dest);
+        Some(dest)
+    }
+
+    /// Forgets `stream`, freeing its share of its destination.
+    pub fn release(&mut self, stream: &K) -> Option<usize> {
+        let dest = self.assignments.remove(stream)?;
+        self.destinations[dest].streams -= 1;
+        Some(dest)
+    }
+
+    /// The destination `stream` is assigned to.
+    pub fn destination_of(&self, stream: &K) -> Option<usize> {
+        self.assignments.get(stream).copied()
+    }
+
+    /// The number of streams assigned to `destination`.
+    pub fn streams_on(&self, destination: usize) -> usize {
+        self.destinations
+            .get(destination)
+            .map_or(0, |dest| dest.streams)
+    }
+}
+
+impl<K: Hash + Eq + Clone> Default for Distributor<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spreads_by_weight_and_rehomes() {
+        let mut distributor = Distributor::new();
+        let heavy = distributor.add_destination(2);
+        let light = distributor.add_destination(1);
+        for stream in 0..6 {
+            distributor.assign(stream);
+        }
+        assert_eq!(distributor.streams_on(heavy), 4);
+        assert_eq!(distributor.streams_on(light), 2);
+
+        let moves = distributor.set_health(heavy, 0);
+        assert_eq!(moves.len(), 4);
+        assert!(moves.iter().all(|m| m.from == heavy && m.to == Some(light)));
+        assert_eq!(distributor.streams_on(light), 6);
+
+        let moves = distributor.set_health(light, 0);
+        assert!(moves.iter().all(|m| m.to.is_none()));
+        assert_eq!(distributor.destination_of(&0), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! Splitting oversized messages into fragments and reassembling them.
+//!
+//! Every message sent through a [`Fragmented`] transport carries an 8 byte
+//! header: a message id (`u32`), the fragment index (`u16`) and the fragment
+//! count (`u16`), all big-endian. Both ends must use the wrapper.
+
+use std::collections::VecDeque;
+use std::io;
+use std::sync::Mutex;
+
+use crate::transport::SrtTransport;
+
+const HEADER_LEN: usize = 8;
+
+/// Partially received messages kept at once; the oldest is abandoned when a
+/// fragment of a further message arrives, as happens when a fragment is lost.
+const MAX_PENDING: usize = 8;
+
+#[derive(Debug)]
+struct Partial {
+    id: u32,
+    fragments: Vec<Option<Vec<u8>>>,
+    missing: usize,
+}
+
+/// A transport wrapper that sends messages larger than the payload size as
+/// numbered fragments and reassembles them on receipt.
+#[derive(Debug)]
+pub struct Fragmented<T> {
+    transport: T,
+    max_payload: usize,
+    next_id: Mutex<u32>,
+    pending: Mutex<VecDeque<Partial>>,
+}
+
+impl<T: SrtTransport> Fragmented<T> {
+    /// Wraps `transport`, whose messages may be at most `max_payload` bytes,
+    /// for example [`LIVE_MAX_PAYLOAD_SIZE`](crate::copy::LIVE_MAX_PAYLOAD_SIZE).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_payload` leaves no room after the fragment header.
+    pub fn new(transport: T, max_payload: usize) -> Self {
+        assert!(max_payload > HEADER_LEN, "payload size too small");
+        Self {
+            transport,
+            max_payload,
+            next_id: Mutex::new(0),
+            pending: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// The largest message that can be sent.
+    pub fn max_message_size(&self) -> usize {
+        (self.max_payload - HEADER_LEN) * usize::from(u16::MAX)
+    }
+
+    /// Sends `message`, split into as many fragments as it needs.
+    pub fn send(&self, message: &[u8]) -> io::Result<()> {
+        let chunk = self.max_payload - HEADER_LEN;
+        let count = message.len().div_ceil(chunk).max(1);
+        let count = u16::try_from(count).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "message too large to fragment")
+        })?;
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            let id = *next_id;
+            *next_id = id.wrapping_add(1);
+            id
+        };
+        let mut buf = Vec::with_capacity(self.max_payload);
+        for index in 0..count {
+            let start = usize::from(index) * chunk;
+            let end = message.len().min(start + chunk);
+            buf.clear();
+            buf.extend_from_slice(&id.to_be_bytes());
+            buf.extend_from_slice(&index.to_be_bytes());
+            buf.extend_from_slice(&count.to_be_bytes());
+            buf.extend_from_slice(&message[start..end]);
+            self.transport.send(&buf)?;
+        }
+        Ok(())
+    }
+
+    /// Waits for the next complete message. Returns `None` once the peer
+    /// has closed.
+    ///
+    /// Messages without a valid fragment header are discarded.
+    pub fn recv(&self) -> io::Result<Option<Vec<u8>>> {
+        let mut buf = vec![0; self.max_payload];
+        loop {
+            let n = self.transport.recv(&mut buf)?;
+            if n == 0 {
+                return Ok(None);
+            }
+            if let Some(message) = self.reassemble(&buf[..n]) {
+                return Ok(Some(message));
+            }
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    fn reassemble(&self, fragment: &[u8]) -> Option<Vec<u8>> {
+        let (header, payload) = fragment.split_at_checked(HEADER_LEN)?;
+        let id = u32::from_be_bytes(header[0..4].try_into().unwrap());
+        let index = usize::from(u16::from_be_bytes(header[4..6].try_into().unwrap()));
+        let count = usize::from(u16::from_be_bytes(header[6..8].try_into().unwrap()));
+        if index >= count {
+            return None;
+        }
+        if count == 1 {
+            return Some(payload.to_vec());
+        }
+
+        let mut pending = self.pending.lock().unwrap();
+        let position = match pending.iter().position(|partial| partial.id == id) {
+            Some(position) => position,
+            None => {
+                if pending.len() == MAX_PENDING {
+                    pending.pop_front();
+                }
+                pending.push_back(Partial {
+                    id,
+                    fragments: vec![None; count],
+                    missing: count,
+                });
+                pending.len() - 1
+            }
+        };
+        let partial = &mut pending[position];
+        if partial.fragments.len() != count {
+            return None;
+        }
+        if partial.fragments[index].is_none() {
+            partial.fragments[index] = Some(payload.to_vec());
+            partial.missing -= 1;
+        }
+        if partial.missing > 0 {
+            return None;
+        }
+        let partial = pending.remove(position).unwrap();
+        Some(partial.fragments.into_iter().flatten().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockConfig, MockTransport};
+
+    fn pair(max_payload: usize) -> (Fragmented<MockTransport>, Fragmented<MockTransport>) {
+        let (a, b) = MockTransport::pair(MockConfig::default());
+        (
+            Fragmented::new(a, max_payload),
+            Fragmented::new(b, max_payload),
+        )
+    }
+
+    #[test]
+    fn round_trips_large_and_small_messages() {
+        let (a, b) = pair(64);
+        let large: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        a.send(&large).unwrap();
+        a.send(b"small").unwrap();
+        a.send(b"").unwrap();
+
+        assert_eq!(b.recv().unwrap().unwrap(), large);
+        assert_eq!(b.recv().unwrap().unwrap(), b"small");
+        assert_eq!(b.recv().unwrap().unwrap(), b"");
+    }
+
+    #[test]
+    fn evicts_the_oldest_incomplete_message() {
+        let (_a, b) = pair(16);
+        let fragment = |id: u32, index: u16| {
+            let mut fragment = id.to_be_bytes().to_vec();
+            fragment.extend_from_slice(&index.to_be_bytes());
+            fragment.extend_from_slice(&2u16.to_be_bytes());
+            fragment.push(index as u8);
+            fragment
+        };
+        for id in 0..=MAX_PENDING as u32 {
+            assert_eq!(b.reassemble(&fragment(id, 0)), None);
+        }
+        assert_eq!(b.pending.lock().unwrap().len(), MAX_PENDING);
+
+        // Message 0 was abandoned, so its second fragment starts it afresh.
+        assert_eq!(b.reassemble(&fragment(0, 1)), None);
+        assert_eq!(b.reassemble(&fragment(2, 1)), Some(vec![0, 1]));
+    }
+}
@@ -0,0 +1,182 @@
+//! Application-level heartbeats for intermittently silent links.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::cancel::{CancelRegistration, CancellationToken};
+use crate::transport::SrtTransport;
+
+/// The payload of a heartbeat message.
+pub const HEARTBEAT: &[u8] = b"\0srt-keepalive";
+
+/// Heartbeat timing for a [`Keepalive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepaliveConfig {
+    /// Send a heartbeat once nothing has been sent for this long.
+    pub interval: Duration,
+    /// Consider the peer gone once nothing has been heard for this long.
+    pub peer_timeout: Duration,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            peer_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    last_sent: Instant,
+    last_heard: Instant,
+    stopped: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+/// Sends [`HEARTBEAT`] messages during gaps in outgoing traffic and tracks
+/// when the peer was last heard from.
+///
+/// The application reports its own traffic through [`record_sent`] and
+/// [`record_received`], so heartbeats are only sent when the link would
+/// otherwise be silent. The background thread stops when this is dropped.
+///
+/// [`record_sent`]: Keepalive::record_sent
+/// [`record_received`]: Keepalive::record_received
+#[derive(Debug)]
+pub struct Keepalive {
+    config: KeepaliveConfig,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+    cancel: Mutex<Option<CancelRegistration>>,
+}
+
+impl Keepalive {
+    pub fn spawn<T>(transport: Arc<T>, config: KeepaliveConfig) -> Self
+    where
+        T: SrtTransport + Send + Sync + 'static,
+    {
+        let now = Instant::now();
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                last_sent: now,
+                last_heard: now,
+                stopped: false,
+            }),
+            wake: Condvar::new(),
+        });
+        let thread = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || run(&*transport, &shared, config.interval))
+        };
+        Self {
+            config,
+            shared,
+            thread: Some(thread),
+            cancel: Mutex::new(None),
+        }
+    }
+
+    /// Notes that the application sent a message, postponing the next
+    /// heartbeat.
+    pub fn record_sent(&self) {
+        self.shared.state.lock().unwrap().last_sent = Instant::now();
+    }
+
+    /// Notes a message received from the peer, returning `true` if it was a
+    /// heartbeat that the application should discard.
+    pub fn record_received(&self, message: &[u8]) -> bool {
+        self.shared.state.lock().unwrap().last_heard = Instant::now();
+        message == HEARTBEAT
+    }
+
+    /// Stops sending heartbeats once `token` is cancelled, without waiting
+    /// for this to be dropped. Replaces any token given before.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        let shared = Arc::downgrade(&self.shared);
+        let registration = token.on_cancel(move || {
+            if let Some(shared) = shared.upgrade() {
+                shared.stop();
+            }
+        });
+        *self.cancel.lock().unwrap() = Some(registration);
+    }
+
+    /// Time since the peer was last heard from.
+    pub fn peer_idle(&self) -> Duration {
+        self.shared.state.lock().unwrap().last_heard.elapsed()
+    }
+
+    /// Whether the peer has been heard from within the peer timeout.
+    pub fn is_peer_alive(&self) -> bool {
+        self.peer_idle() < self.config.peer_timeout
+    }
+}
+
+impl Drop for Keepalive {
+    fn drop(&mut self) {
+        self.shared.stop();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Shared {
+    fn stop(&self) {
+        self.state.lock().unwrap().stopped = true;
+        self.wake.notify_all();
+    }
+}
+
+fn run<T: SrtTransport + ?Sized>(transport: &T, shared: &Shared, interval: Duration) {
+    let mut state = shared.state.lock().unwrap();
+    while !state.stopped {
+        let due = state.last_sent + interval;
+        let now = Instant::now();
+        if now < due {
+            state = shared.wake.wait_timeout(state, due - now).unwrap().0;
+            continue;
+        }
+        state.last_sent = now;
+        drop(state);
+        if transport.send(HEARTBEAT).is_err() {
+            return;
+        }
+        state = shared.state.lock().unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockConfig, MockTransport};
+
+    #[test]
+    fn sends_heartbeats_when_idle() {
+        let (local, peer) = MockTransport::pair(MockConfig::default());
+        let config = KeepaliveConfig {
+            interval: Duration::from_millis(10),
+            peer_timeout: Duration::from_millis(50),
+        };
+        let keepalive = Keepalive::spawn(Arc::new(local), config);
+
+        let mut buf = [0; 32];
+        let n = peer
+            .recv_timeout(&mut buf, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert!(keepalive.record_received(&buf[..n]));
+        assert!(keepalive.is_peer_alive());
+        assert!(!keepalive.record_received(b"data"));
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(!keepalive.is_peer_alive());
+    }
+}
@@ -0,0 +1,97 @@
+//! Receiver latency recommendations.
+//!
+//! SRT needs enough latency to retransmit lost packets before they are due
+//! for playback. The usual rule of thumb sets it to a multiple of the round
+//! trip time, with a larger multiple the more packets the link loses:
+//!
+//! | Loss        | Multiplier |
+//! |-------------|------------|
+//! | up to 1%    | 3          |
+//! | up to 3%    | 4          |
+//! | up to 7%    | 6          |
+//! | above 7%    | 8          |
+//!
+//! Links losing more than 10% are poorly served by any latency.
+
+use std::time::Duration;
+
+use crate::probe::ProbeReport;
+use crate::stats::Stats;
+
+/// The smallest latency recommended, however short the round trip. Below
+/// this, scheduling and network jitter rather than retransmissions dominate.
+pub const MIN_LATENCY: Duration = Duration::from_millis(80);
+
+/// The RTT multiplier for a loss ratio between 0 and 1.
+pub fn rtt_multiplier(loss: f64) -> u32 {
+    match loss {
+        loss if loss <= 0.01 => 3,
+        loss if loss <= 0.03 => 4,
+        loss if loss <= 0.07 => 6,
+        _ => 8,
+    }
+}
+
+/// Suggests a receiver latency (`SRTO_LATENCY`) for a link with round trip
+/// time `rtt` and a loss ratio of `loss`, no lower than [`MIN_LATENCY`].
+pub fn recommended_latency(rtt: Duration, loss: f64) -> Duration {
+    rtt.saturating_mul(rtt_multiplier(loss)).max(MIN_LATENCY)
+}
+
+/// Suggests a latency from the smoothed RTT and lifetime loss counters of a
+/// connected socket. Loss in both directions is counted. An RTT that is
+/// negative or not finite is treated as unmeasured, giving [`MIN_LATENCY`].
+pub fn from_stats(stats: &Stats) -> Duration {
+    let rtt = Duration::try_from_secs_f64(stats.ms_rtt / 1000.0).unwrap_or(Duration::ZERO);
+    let lost = stats.pkt_snd_loss_total as f64 + stats.pkt_rcv_loss_total as f64;
+    let total = stats.pkt_sent_total as f64 + stats.pkt_recv_total as f64;
+    let loss = if total > 0.0 { lost / total } else { 0.0 };
+    recommended_latency(rtt, loss)
+}
+
+/// Suggests a latency from a [`ping`](crate::probe::ping) run, or `None` if
+/// no probe was answered.
+pub fn from_probe(report: &ProbeReport) -> Option<Duration> {
+    Some(recommended_latency(report.rtt_avg()?, report.loss()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_rtt_by_loss() {
+        let rtt = Duration::from_millis(100);
+        assert_eq!(recommended_latency(rtt, 0.0), Duration::from_millis(300));
+        assert_eq!(recommended_latency(rtt, 0.05), Duration::from_millis(600));
+        assert_eq!(recommended_latency(rtt, 0.5), Duration::from_millis(800));
+        assert_eq!(
+            recommended_latency(Duration::from_millis(5), 0.0),
+            MIN_LATENCY
+        );
+    }
+
+    #[test]
+    fn uses_stats_and_probes() {
+        let stats = Stats {
+            ms_rtt: 40.0,
+            pkt_sent_total: 980,
+            pkt_recv_total: 20,
+            pkt_snd_loss_total: 20,
+            ..Stats::default()
+        };
+        assert_eq!(from_stats(&stats), Duration::from_millis(160));
+        for ms_rtt in [f64::NAN, f64::INFINITY, -1.0] {
+            let stats = Stats { ms_rtt, ..stats };
+            assert_eq!(from_stats(&stats), MIN_LATENCY);
+        }
+
+        assert_eq!(from_probe(&ProbeReport::default()), None);
+        let report = ProbeReport {
+            sent: 2,
+            rtts: vec![Duration::from_millis(50)],
+            one_way: Vec::new(),
+        };
+        assert_eq!(from_probe(&report), Some(Duration::from_millis(400)));
+    }
+}
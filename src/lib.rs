@@ -1,14 +1,30 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub mod access;
+pub mod cancel;
+pub mod channel;
+pub mod copy;
+pub mod counters;
+pub mod crypto;
+pub mod dedup;
+pub mod diagnostics;
+pub mod distribute;
+pub mod fragment;
+pub mod keepalive;
+pub mod latency;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod options;
+pub mod pacing;
+pub mod pool;
+pub mod probe;
+pub mod profile;
+pub mod queue;
+pub mod reconnect;
+pub mod reject;
+pub mod resolver;
+pub mod ring;
+pub mod scoped;
+pub mod server;
+pub mod stats;
+pub mod timer;
+pub mod transport;
+pub mod unidirectional;
@@ -0,0 +1,379 @@
+//! An in-memory [`SrtTransport`] for deterministic tests.
+//!
+//! Connections never touch the network. [`MockListener::bind`] registers an
+//! address in a process-wide table, and [`SrtTransport::connect`] on
+//! [`MockTransport`] connects to it. Each listener's [`MockConfig`] controls
+//! the loss, latency and rejection its connections see.
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::counters::counters;
+use crate::reject::RejectReason;
+use crate::transport::SrtTransport;
+
+/// Simulated link behaviour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockConfig {
+    /// Probability, from 0 to 1, that a sent message is lost.
+    pub loss: f64,
+    /// Delay before a sent message becomes available to the peer.
+    pub latency: Duration,
+    /// Seed for the loss generator, so runs are reproducible.
+    pub seed: u64,
+    /// If set, connection attempts are refused with this reason.
+    pub reject: Option<RejectReason>,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            loss: 0.0,
+            latency: Duration::ZERO,
+            seed: 0x5eed,
+            reject: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Pipe {
+    state: Mutex<PipeState>,
+    readable: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct PipeState {
+    messages: VecDeque<(Instant, Vec<u8>)>,
+    closed: bool,
+}
+
+impl Pipe {
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.readable.notify_all();
+    }
+}
+
+/// One end of an in-memory connection.
+#[derive(Debug)]
+pub struct MockTransport {
+    local: SocketAddr,
+    peer: SocketAddr,
+    outbound: Arc<Pipe>,
+    inbound: Arc<Pipe>,
+    config: MockConfig,
+    rng: Mutex<u64>,
+}
+
+impl MockTransport {
+    /// Creates two transports connected to each other.
+    pub fn pair(config: MockConfig) -> (MockTransport, MockTransport) {
+        let (a, b) = {
+            let listeners = registry().lock().unwrap();
+            (
+                next_addr(&NEXT_PORT, &listeners),
+                next_addr(&NEXT_PORT, &listeners),
+            )
+        };
+        let (a_to_b, b_to_a) = (Arc::<Pipe>::default(), Arc::<Pipe>::default());
+        let end = |local, peer, outbound: &Arc<Pipe>, inbound: &Arc<Pipe>, seed| MockTransport {
+            local,
+            peer,
+            outbound: Arc::clone(outbound),
+            inbound: Arc::clone(inbound),
+            config,
+            rng: Mutex::new(seed),
+        };
+        (
+            end(a, b, &a_to_b, &b_to_a, config.seed),
+            end(b, a, &b_to_a, &a_to_b, !config.seed),
+        )
+    }
+
+    fn lost(&self) -> bool {
+        if self.config.loss <= 0.0 {
+            return false;
+        }
+        let mut state = self.rng.lock().unwrap();
+        // xorshift64*, seeded per end so both directions are reproducible.
+        let mut x = (*state).max(1);
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        *state = x;
+        let sample = (x.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64;
+        sample < self.config.loss
+    }
+
+    fn send_message(&self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.outbound.state.lock().unwrap();
+        if state.closed {
+            return Err(io::ErrorKind::NotConnected.into());
+        }
+        if !self.lost() {
+            let due = Instant::now() + self.config.latency;
+            state.messages.push_back((due, buf.to_vec()));
+            self.outbound.readable.notify_all();
+        }
+        Ok(buf.len())
+    }
+
+    fn recv_message(&self, buf: &mut [u8], timeout: Option<Duration>) -> io::Result<usize> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut state = self.inbound.state.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let due = state.messages.front().map(|(due, _)| *due);
+            if due.is_some_and(|due| due <= now) {
+                let (_, message) = state.messages.front().unwrap();
+                // Like libsrt in message mode, never truncate; the message
+                // stays queued for a receive with a large enough buffer.
+                let Some(buf) = buf.get_mut(..message.len()) else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "receive buffer is smaller than the message",
+                    ));
+                };
+                buf.copy_from_slice(message);
+                let n = message.len();
+                state.messages.pop_front();
+                return Ok(n);
+            }
+            if state.closed && due.is_none() {
+                return Ok(0);
+            }
+            let wake = match (due, deadline) {
+                (Some(due), Some(deadline)) => Some(due.min(deadline)),
+                (due, deadline) => due.or(deadline),
+            };
+            if deadline.is_some_and(|deadline| deadline <= now) {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            state = match wake {
+                Some(wake) => {
+                    let wait = wake.saturating_duration_since(now);
+                    self.inbound.readable.wait_timeout(state, wait).unwrap().0
+                }
+                None => self.inbound.readable.wait(state).unwrap(),
+            };
+        }
+    }
+}
+
+impl SrtTransport for MockTransport {
+    fn connect(addr: SocketAddr) -> io::Result<Self> {
+        let listener = registry()
+            .lock()
+            .unwrap()
+            .get(&addr)
+            .cloned()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::ConnectionRefused))?;
+        if let Some(reason) = listener.config.reject {
+            return Err(io::Error::new(io::ErrorKind::ConnectionRefused, reason));
+        }
+        let (mut client, mut server) = MockTransport::pair(listener.config);
+        client.peer = addr;
+        server.local = addr;
+        server.peer = client.local;
+        listener.pending.lock().unwrap().push_back(server);
+        listener.incoming.notify_one();
+        Ok(client)
+    }
+
+    fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        let result = self.send_message(buf);
+        counters().record_send(&result);
+        result
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv_timeout(buf, None)
+    }
+
+    fn recv_timeout(&self, buf: &mut [u8], timeout: Option<Duration>) -> io::Result<usize> {
+        let result = self.recv_message(buf, timeout);
+        counters().record_recv(&result);
+        result
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.local)
+    }
+
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.peer)
+    }
+}
+
+impl Drop for MockTransport {
+    fn drop(&mut self) {
+        self.outbound.close();
+        self.inbound.close();
+    }
+}
+
+#[derive(Debug)]
+struct ListenerInner {
+    config: MockConfig,
+    pending: Mutex<VecDeque<MockTransport>>,
+    incoming: Condvar,
+}
+
+/// Accepts in-memory connections made with [`SrtTransport::connect`].
+#[derive(Debug)]
+pub struct MockListener {
+    addr: SocketAddr,
+    inner: Arc<ListenerInner>,
+}
+
+impl MockListener {
+    /// Registers a listener at `addr`. Port 0 picks an unused port.
+    pub fn bind(addr: SocketAddr, config: MockConfig) -> io::Result<Self> {
+        let mut listeners = registry().lock().unwrap();
+        let addr = match addr.port() {
+            0 => next_addr(&NEXT_PORT, &listeners),
+            _ => addr,
+        };
+        let inner = Arc::new(ListenerInner {
+            config,
+            pending: Mutex::new(VecDeque::new()),
+            incoming: Condvar::new(),
+        });
+        if listeners.contains_key(&addr) {
+            return Err(io::ErrorKind::AddrInUse.into());
+        }
+        listeners.insert(addr, Arc::clone(&inner));
+        Ok(Self { addr, inner })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Waits for the next incoming connection.
+    pub fn accept(&self) -> io::Result<MockTransport> {
+        let mut pending = self.inner.pending.lock().unwrap();
+        loop {
+            if let Some(conn) = pending.pop_front() {
+                return Ok(conn);
+            }
+            pending = self.inner.incoming.wait(pending).unwrap();
+        }
+    }
+}
+
+impl Drop for MockListener {
+    fn drop(&mut self) {
+        registry().lock().unwrap().remove(&self.addr);
+    }
+}
+
+fn registry() -> &'static Mutex<HashMap<SocketAddr, Arc<ListenerInner>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<SocketAddr, Arc<ListenerInner>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+static NEXT_PORT: AtomicU16 = AtomicU16::new(40000);
+
+/// Hands out loopback addresses from `counter` in turn, skipping port 0 when
+/// the counter wraps and any address already `taken`, such as one a listener
+/// was bound to explicitly.
+fn next_addr<V>(counter: &AtomicU16, taken: &HashMap<SocketAddr, V>) -> SocketAddr {
+    loop {
+        let port = counter.fetch_add(1, Ordering::Relaxed);
+        let addr = (Ipv4Addr::LOCALHOST, port).into();
+        if port != 0 && !taken.contains_key(&addr) {
+            return addr;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connects_through_listener() {
+        let listener =
+            MockListener::bind((Ipv4Addr::LOCALHOST, 0).into(), MockConfig::default()).unwrap();
+        let client = MockTransport::connect(listener.local_addr()).unwrap();
+        let server = listener.accept().unwrap();
+        assert_eq!(server.peer_addr().unwrap(), client.local_addr().unwrap());
+
+        client.send(b"one").unwrap();
+        client.send(b"two").unwrap();
+        let mut buf = [0; 8];
+        assert_eq!(server.recv(&mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], b"one");
+        assert_eq!(server.recv(&mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], b"two");
+
+        drop(client);
+        assert_eq!(server.recv(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn rejects_with_configured_reason() {
+        let config = MockConfig {
+            reject: Some(RejectReason::BadSecret),
+            ..MockConfig::default()
+        };
+        let listener = MockListener::bind((Ipv4Addr::LOCALHOST, 0).into(), config).unwrap();
+        let err = MockTransport::connect(listener.local_addr()).unwrap_err();
+        let reason = err.get_ref().unwrap().downcast_ref::<RejectReason>();
+        assert_eq!(reason, Some(&RejectReason::BadSecret));
+    }
+
+    #[test]
+    fn loss_and_latency_are_applied() {
+        let config = MockConfig {
+            loss: 0.5,
+            latency: Duration::from_millis(20),
+            ..MockConfig::default()
+        };
+        let (a, b) = MockTransport::pair(config);
+        let start = Instant::now();
+        for i in 0..100u8 {
+            a.send(&[i]).unwrap();
+        }
+        drop(a);
+
+        let mut buf = [0; 1];
+        let mut received = 0;
+        while b.recv(&mut buf).unwrap() != 0 {
+            received += 1;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert!((30..70).contains(&received), "received {received}");
+
+        let (a, _b) = MockTransport::pair(MockConfig::default());
+        let err = a.recv_timeout(&mut buf, Some(Duration::from_millis(5)));
+        assert_eq!(err.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn generated_addresses_skip_port_zero_and_bound_ports() {
+        let counter = AtomicU16::new(u16::MAX);
+        let taken = HashMap::from([((Ipv4Addr::LOCALHOST, 1).into(), ())]);
+        let ports: Vec<_> = (0..3).map(|_| next_addr(&counter, &taken).port()).collect();
+        assert_eq!(ports, [u16::MAX, 2, 3]);
+    }
+
+    #[test]
+    fn rejects_buffers_smaller_than_the_message() {
+        let (a, b) = MockTransport::pair(MockConfig::default());
+        a.send(b"message").unwrap();
+        let mut small = [0; 4];
+        let err = b.recv(&mut small).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let mut buf = [0; 16];
+        let n = b.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"message");
+    }
+}
@@ -0,0 +1,957 @@
+//! Socket options and version-aware option sets.
+
+use std::fmt;
+
+use crate::crypto::{InvalidPassphrase, Passphrase};
+
+/// A libsrt version, as reported by `srt_getversion`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl Version {
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Decodes libsrt's packed `0xMMmmpp` representation.
+    pub const fn from_raw(raw: u32) -> Self {
+        Self::new((raw >> 16) as u8, (raw >> 8) as u8, raw as u8)
+    }
+
+    /// Encodes the version in libsrt's packed `0xMMmmpp` representation.
+    pub const fn to_raw(self) -> u32 {
+        (self.major as u32) << 16 | (self.minor as u32) << 8 | self.patch as u32
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A socket option, with discriminants matching libsrt's `SRT_SOCKOPT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SockOpt {
+    Mss = 0,
+    SndSyn = 1,
+    RcvSyn = 2,
+    Isn = 3,
+    Fc = 4,
+    SndBuf = 5,
+    RcvBuf = 6,
+    Linger = 7,
+    UdpSndBuf = 8,
+    UdpRcvBuf = 9,
+    Rendezvous = 12,
+    SndTimeo = 13,
+    RcvTimeo = 14,
+    ReuseAddr = 15,
+    MaxBw = 16,
+    State = 17,
+    Event = 18,
+    SndData = 19,
+    RcvData = 20,
+    Sender = 21,
+    TsbpdMode = 22,
+    Latency = 23,
+    InputBw = 24,
+    OheadBw = 25,
+    Passphrase = 26,
+    PbKeyLen = 27,
+    KmState = 28,
+    IpTtl = 29,
+    IpTos = 30,
+    TlPktDrop = 31,
+    SndDropDelay = 32,
+    NakReport = 33,
+    Version = 34,
+    PeerVersion = 35,
+    ConnTimeo = 36,
+    DriftTracer = 37,
+    MinInputBw = 38,
+    SndKmState = 40,
+    RcvKmState = 41,
+    LossMaxTtl = 42,
+    RcvLatency = 43,
+    PeerLatency = 44,
+    MinVersion = 45,
+    StreamId = 46,
+    Congestion = 47,
+    MessageApi = 48,
+    PayloadSize = 49,
+    TransType = 50,
+    KmRefreshRate = 51,
+    KmPreAnnounce = 52,
+    EnforcedEncryption = 53,
+    Ipv6Only = 54,
+    PeerIdleTimeo = 55,
+    BindToDevice = 56,
+    GroupConnect = 57,
+    GroupMinStableTimeo = 58,
+    GroupType = 59,
+    PacketFilter = 60,
+    RetransmitAlgo = 61,
+    CryptoMode = 62,
+}
+
+impl SockOpt {
+    /// The numeric `SRTO_*` value.
+    pub fn raw(self) -> i32 {
+        self as i32
+    }
+
+    /// Whether the option holds a secret that must not be logged.
+    pub fn is_secret(self) -> bool {
+        self == SockOpt::Passphrase
+    }
+
+    /// The first libsrt release that understands this option.
+    pub fn since(self) -> Version {
+        match self {
+            SockOpt::ConnTimeo => Version::new(1, 1, 2),
+            SockOpt::SndKmState | SockOpt::RcvKmState | SockOpt::LossMaxTtl => {
+                Version::new(1, 2, 0)
+            }
+            SockOpt::RcvLatency
+            | SockOpt::PeerLatency
+            | SockOpt::MinVersion
+            | SockOpt::StreamId
+            | SockOpt::Congestion
+            | SockOpt::MessageApi
+            | SockOpt::PayloadSize
+            | SockOpt::TransType => Version::new(1, 3, 0),
+            SockOpt::KmRefreshRate | SockOpt::KmPreAnnounce | SockOpt::EnforcedEncryption => {
+                Version::new(1, 3, 2)
+            }
+            SockOpt::PeerIdleTimeo => Version::new(1, 3, 3),
+            SockOpt::Ipv6Only | SockOpt::PacketFilter => Version::new(1, 4, 0),
+            SockOpt::DriftTracer | SockOpt::BindToDevice | SockOpt::RetransmitAlgo => {
+                Version::new(1, 4, 2)
+            }
+            SockOpt::MinInputBw => Version::new(1, 4, 3),
+            SockOpt::GroupConnect | SockOpt::GroupMinStableTimeo | SockOpt::GroupType => {
+                Version::new(1, 5, 0)
+            }
+            SockOpt::CryptoMode => Version::new(1, 5, 2),
+            _ => Version::new(1, 0, 0),
+        }
+    }
+
+    /// Whether libsrt `version` understands this option.
+    pub fn is_supported_by(self, version: Version) -> bool {
+        version >= self.since()
+    }
+
+    /// The name used for this option in SRT URIs and option strings, if it
+    /// can be set that way.
+    pub fn name(self) -> Option<&'static str> {
+        NAMED
+            .iter()
+            .find(|(_, opt, _)| *opt == self)
+            .map(|(name, _, _)| *name)
+    }
+
+    /// Looks up an option by its SRT URI name, ignoring ASCII case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        NAMED
+            .iter()
+            .find(|(n, _, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, opt, _)| *opt)
+    }
+
+    fn kind(self) -> Option<OptionKind> {
+        NAMED
+            .iter()
+            .find(|(_, opt, _)| *opt == self)
+            .map(|(_, _, kind)| *kind)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OptionKind {
+    Int,
+    Bool,
+    Str,
+}
+
+/// Options settable from strings, by the names documented for SRT URIs.
+const NAMED: &[(&str, SockOpt, OptionKind)] = &[
+    ("bindtodevice", SockOpt::BindToDevice, OptionKind::Str),
+    ("congestion", SockOpt::Congestion, OptionKind::Str),
+    ("conntimeo", SockOpt::ConnTimeo, OptionKind::Int),
+    ("cryptomode", SockOpt::CryptoMode, OptionKind::Int),
+    ("drifttracer", SockOpt::DriftTracer, OptionKind::Bool),
+    (
+        "enforcedencryption",
+        SockOpt::EnforcedEncryption,
+        OptionKind::Bool,
+    ),
+    ("fc", SockOpt::Fc, OptionKind::Int),
+    ("groupconnect", SockOpt::GroupConnect, OptionKind::Bool),
+    (
+        "groupminstabletimeo",
+        SockOpt::GroupMinStableTimeo,
+        OptionKind::Int,
+    ),
+    ("inputbw", SockOpt::InputBw, OptionKind::Int),
+    ("iptos", SockOpt::IpTos, OptionKind::Int),
+    ("ipttl", SockOpt::IpTtl, OptionKind::Int),
+    ("ipv6only", SockOpt::Ipv6Only, OptionKind::Int),
+    ("kmpreannounce", SockOpt::KmPreAnnounce, OptionKind::Int),
+    ("kmrefreshrate", SockOpt::KmRefreshRate, OptionKind::Int),
+    ("latency", SockOpt::Latency, OptionKind::Int),
+    ("linger", SockOpt::Linger, OptionKind::Int),
+    ("lossmaxttl", SockOpt::LossMaxTtl, OptionKind::Int),
+    ("maxbw", SockOpt::MaxBw, OptionKind::Int),
+    ("messageapi", SockOpt::MessageApi, OptionKind::Bool),
+    ("mininputbw", SockOpt::MinInputBw, OptionKind::Int),
+    ("minversion", SockOpt::MinVersion, OptionKind::Int),
+    ("mss", SockOpt::Mss, OptionKind::Int),
+    ("nakreport", SockOpt::NakReport, OptionKind::Bool),
+    ("oheadbw", SockOpt::OheadBw, OptionKind::Int),
+    ("packetfilter", SockOpt::PacketFilter, OptionKind::Str),
+    ("passphrase", SockOpt::Passphrase, OptionKind::Str),
+    ("payloadsize", SockOpt::PayloadSize, OptionKind::Int),
+    ("pbkeylen", SockOpt::PbKeyLen, OptionKind::Int),
+    ("peeridletimeo", SockOpt::PeerIdleTimeo, OptionKind::Int),
+    ("peerlatency", SockOpt::PeerLatency, OptionKind::Int),
+    ("rcvbuf", SockOpt::RcvBuf, OptionKind::Int),
+    ("rcvlatency", SockOpt::RcvLatency, OptionKind::Int),
+    ("retransmitalgo", SockOpt::RetransmitAlgo, OptionKind::Int),
+    ("sndbuf", SockOpt::SndBuf, OptionKind::Int),
+    ("snddropdelay", SockOpt::SndDropDelay, OptionKind::Int),
+    ("streamid", SockOpt::StreamId, OptionKind::Str),
+    ("tlpktdrop", SockOpt::TlPktDrop, OptionKind::Bool),
+    ("transtype", SockOpt::TransType, OptionKind::Str),
+    ("tsbpdmode", SockOpt::TsbpdMode, OptionKind::Bool),
+];
+
+/// The value of a socket option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionValue {
+    Int(i64),
+    Bool(bool),
+    Str(String),
+}
+
+impl From<i64> for OptionValue {
+    fn from(value: i64) -> Self {
+        OptionValue::Int(value)
+    }
+}
+
+impl From<bool> for OptionValue {
+    fn from(value: bool) -> Self {
+        OptionValue::Bool(value)
+    }
+}
+
+impl From<&str> for OptionValue {
+    fn from(value: &str) -> Self {
+        OptionValue::Str(value.to_owned())
+    }
+}
+
+impl From<String> for OptionValue {
+    fn from(value: String) -> Self {
+        OptionValue::Str(value)
+    }
+}
+
+/// A transmission type, the value of `SRTO_TRANSTYPE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransType {
+    Live,
+    File,
+}
+
+impl TransType {
+    /// The name used in option strings and URIs.
+    pub fn name(self) -> &'static str {
+        match self {
+            TransType::Live => "live",
+            TransType::File => "file",
+        }
+    }
+
+    /// The options libsrt applies when `SRTO_TRANSTYPE` is set to this
+    /// type, which later options then override.
+    pub fn defaults(self) -> OptionSet {
+        let (messageapi, tsbpd, latency, drop, payload, congestion, linger) = match self {
+            TransType::Live => (true, true, 120, 0, 1316, "live", 0),
+            TransType::File => (false, false, 0, -1, 0, "file", 180),
+        };
+        let mut options = OptionSet::new();
+        options
+            .set(SockOpt::TransType, self.name())
+            .and_then(|o| o.set(SockOpt::MessageApi, messageapi))
+            .and_then(|o| o.set(SockOpt::TsbpdMode, tsbpd))
+            .and_then(|o| o.set(SockOpt::RcvLatency, latency))
+            .and_then(|o| o.set(SockOpt::PeerLatency, latency))
+            .and_then(|o| o.set(SockOpt::TlPktDrop, tsbpd))
+            .and_then(|o| o.set(SockOpt::SndDropDelay, drop))
+            .and_then(|o| o.set(SockOpt::NakReport, tsbpd))
+            .and_then(|o| o.set(SockOpt::PayloadSize, payload))
+            .and_then(|o| o.set(SockOpt::Congestion, congestion))
+            .and_then(|o| o.set(SockOpt::Linger, linger))
+            .expect("an unversioned set accepts every option");
+        options
+    }
+}
+
+/// Error returned when setting an option the target libsrt does not support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedOption {
+    pub option: SockOpt,
+    pub since: Version,
+    pub version: Version,
+}
+
+impl fmt::Display for UnsupportedOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} requires libsrt {} but {} is linked",
+            self.option, self.since, self.version
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedOption {}
+
+/// A configuration mistake, as opposed to a failure of the transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SrtConfigError {
+    /// The passphrase is not between 10 and 79 bytes long.
+    InvalidPassphraseLength(usize),
+    /// The two options cannot be used together with their current values.
+    IncompatibleOptions { a: SockOpt, b: SockOpt },
+    /// The option can only be set before the socket connects.
+    OptionSetTooLate(SockOpt),
+    /// The option's value lies outside the range libsrt accepts.
+    ValueOutOfRange {
+        option: SockOpt,
+        value: i64,
+        min: i64,
+        max: i64,
+    },
+}
+
+impl fmt::Display for SrtConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SrtConfigError::InvalidPassphraseLength(len) => InvalidPassphrase { len: *len }.fmt(f),
+            SrtConfigError::IncompatibleOptions { a, b } => {
+                write!(f, "{a:?} cannot be combined with {b:?}")
+            }
+            SrtConfigError::OptionSetTooLate(option) => {
+                write!(f, "{option:?} must be set before connecting")
+            }
+            SrtConfigError::ValueOutOfRange {
+                option,
+                value,
+                min,
+                max,
+            } => write!(f, "{option:?} value {value} is outside {min}..={max}"),
+        }
+    }
+}
+
+impl std::error::Error for SrtConfigError {}
+
+impl From<InvalidPassphrase> for SrtConfigError {
+    fn from(err: InvalidPassphrase) -> Self {
+        SrtConfigError::InvalidPassphraseLength(err.len)
+    }
+}
+
+/// Accepted ranges of integer options, checked by [`OptionSet::validate`].
+const RANGES: &[(SockOpt, i64, i64)] = &[
+    (SockOpt::Mss, 76, 1500),
+    (SockOpt::PayloadSize, 0, 1456),
+    (SockOpt::Latency, 0, i32::MAX as i64),
+    (SockOpt::RcvLatency, 0, i32::MAX as i64),
+    (SockOpt::PeerLatency, 0, i32::MAX as i64),
+    (SockOpt::ConnTimeo, 0, i32::MAX as i64),
+    (SockOpt::OheadBw, 5, 100),
+    (SockOpt::IpTtl, 1, 255),
+    (SockOpt::IpTos, 0, 255),
+];
+
+/// Error returned by [`OptionSet::apply_options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseOptionError {
+    /// A `key=value` pair was expected.
+    MissingValue(String),
+    /// The key does not name a settable option.
+    UnknownOption(String),
+    /// The value could not be parsed for the option's type.
+    InvalidValue { option: SockOpt, value: String },
+    /// The option is not supported by the target libsrt version.
+    Unsupported(UnsupportedOption),
+}
+
+impl fmt::Display for ParseOptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseOptionError::MissingValue(segment) => {
+                write!(f, "expected key=value, found {segment:?}")
+            }
+            ParseOptionError::UnknownOption(name) => write!(f, "unknown option {name:?}"),
+            ParseOptionError::InvalidValue { option, value } => {
+                write!(f, "invalid value {value:?} for {option:?}")
+            }
+            ParseOptionError::Unsupported(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParseOptionError {}
+
+impl From<UnsupportedOption> for ParseOptionError {
+    fn from(err: UnsupportedOption) -> Self {
+        ParseOptionError::Unsupported(err)
+    }
+}
+
+fn parse_value(
+    option: SockOpt,
+    kind: OptionKind,
+    value: &str,
+) -> Result<OptionValue, ParseOptionError> {
+    let invalid = || ParseOptionError::InvalidValue {
+        option,
+        value: value.to_owned(),
+    };
+    match kind {
+        OptionKind::Int => value.parse().map(OptionValue::Int).map_err(|_| invalid()),
+        OptionKind::Bool => match value.to_ascii_lowercase().as_str() {
+            "1" | "yes" | "true" | "on" => Ok(OptionValue::Bool(true)),
+            "0" | "no" | "false" | "off" => Ok(OptionValue::Bool(false)),
+            _ => Err(invalid()),
+        },
+        OptionKind::Str => Ok(OptionValue::Str(value.to_owned())),
+    }
+}
+
+/// An option value as recorded in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditedValue {
+    Value(OptionValue),
+    /// The value of a secret option such as `SRTO_PASSPHRASE`, withheld so
+    /// the log can be printed or stored safely.
+    Redacted,
+}
+
+impl AuditedValue {
+    fn record(option: SockOpt, value: &OptionValue) -> Self {
+        if option.is_secret() {
+            AuditedValue::Redacted
+        } else {
+            AuditedValue::Value(value.clone())
+        }
+    }
+}
+
+/// One attempt to set an option, as recorded by an audited [`OptionSet`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionChange {
+    pub option: SockOpt,
+    /// The value replaced, if the option was already set.
+    pub old: Option<AuditedValue>,
+    pub new: AuditedValue,
+    /// The label of the configuration layer that made the change, as given
+    /// to [`OptionSet::audit_source`].
+    pub source: Option<String>,
+    pub result: Result<(), UnsupportedOption>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Audit {
+    source: Option<String>,
+    log: Vec<OptionChange>,
+}
+
+/// An ordered collection of options to apply to a socket.
+///
+/// Options are kept in the order they were first set; setting an option
+/// again replaces its value in place.
+///
+/// Auditing, once enabled, records every attempted change with its old and
+/// new values, to trace which configuration layer set an effective value.
+/// The audit log does not take part in comparisons. Secret values are
+/// left out of both the audit log and the `Debug` output.
+#[derive(Clone, Default)]
+pub struct OptionSet {
+    version: Option<Version>,
+    options: Vec<(SockOpt, OptionValue)>,
+    audit: Option<Audit>,
+}
+
+impl PartialEq for OptionSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version && self.options == other.options
+    }
+}
+
+impl Eq for OptionSet {}
+
+impl fmt::Debug for OptionSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options: Vec<_> = self
+            .options
+            .iter()
+            .map(|(option, value)| (option, AuditedValue::record(*option, value)))
+            .collect();
+        f.debug_struct("OptionSet")
+            .field("version", &self.version)
+            .field("options", &options)
+            .field("audit", &self.audit)
+            .finish()
+    }
+}
+
+impl OptionSet {
+    /// Creates an empty set that accepts every option.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty set that rejects options `version` does not support.
+    pub fn for_version(version: Version) -> Self {
+        Self {
+            version: Some(version),
+            ..Self::default()
+        }
+    }
+
+    /// Creates a set tuned for small, latency-critical messages such as
+    /// telemetry or scoreboard data, delivered `latency_ms` after sending.
+    ///
+    /// Each knob can be overridden afterwards:
+    ///
+    /// * `transtype=live` and `congestion=live`: no file mode batching.
+    /// * `messageapi=1`: one send is one message.
+    /// * `latency`: the receiver buffer, to be sized from the link RTT, for
+    ///   example with [`latency::recommended_latency`].
+    /// * `payloadsize=1456`: every message up to the live mode maximum goes
+    ///   out as a single packet.
+    /// * `tlpktdrop=1`, `snddropdelay=0`: a message that misses its deadline
+    ///   is dropped rather than delaying the ones behind it.
+    /// * `nakreport=1`: losses are re-reported periodically, so a lost
+    ///   retransmission is recovered without waiting for a timeout.
+    ///
+    /// # Latency
+    ///
+    /// The receiver hands each message over `latency_ms` after it was sent,
+    /// as measured on the sender's clock, so the delivery delay is
+    /// `latency_ms` whatever the link's jitter. A message still missing at
+    /// that point is dropped rather than delivered late. Sending adds no
+    /// batching delay on top: a message of up to 1456 bytes leaves as one
+    /// packet as soon as the live pacing allows.
+    ///
+    /// [`latency::recommended_latency`]: crate::latency::recommended_latency
+    pub fn low_latency_messages(latency_ms: i64) -> Self {
+        let mut options = Self::new();
+        options
+            .apply_options(&format!(
+                "transtype=live,congestion=live,messageapi=1,latency={latency_ms},\
+                 payloadsize=1456,tlpktdrop=1,snddropdelay=0,nakreport=1"
+            ))
+            .expect("preset options are valid");
+        options
+    }
+
+    /// Sets `option` to `value`.
+    pub fn set(
+        &mut self,
+        option: SockOpt,
+        value: impl Into<OptionValue>,
+    ) -> Result<&mut Self, UnsupportedOption> {
+        let value = value.into();
+        let result = match self.version {
+            Some(version) if !option.is_supported_by(version) => Err(UnsupportedOption {
+                option,
+                since: option.since(),
+                version,
+            }),
+            _ => Ok(()),
+        };
+        let old = self
+            .get(option)
+            .map(|old| AuditedValue::record(option, old));
+        if let Some(audit) = &mut self.audit {
+            audit.log.push(OptionChange {
+                option,
+                old,
+                new: AuditedValue::record(option, &value),
+                source: audit.source.clone(),
+                result,
+            });
+        }
+        result?;
+        match self.options.iter_mut().find(|(opt, _)| *opt == option) {
+            Some((_, existing)) => *existing = value,
+            None => self.options.push((option, value)),
+        }
+        Ok(self)
+    }
+
+    /// Starts recording every attempted change in the audit log.
+    pub fn enable_audit(&mut self) -> &mut Self {
+        self.audit.get_or_insert_with(Audit::default);
+        self
+    }
+
+    /// Labels subsequent changes with `source`, such as a configuration
+    /// file name, enabling auditing if it is not already enabled.
+    pub fn audit_source(&mut self, source: impl Into<String>) -> &mut Self {
+        self.audit.get_or_insert_with(Audit::default).source = Some(source.into());
+        self
+    }
+
+    /// The changes recorded since auditing was enabled, oldest first.
+    pub fn audit_log(&self) -> &[OptionChange] {
+        self.audit.as_ref().map_or(&[], |audit| &audit.log)
+    }
+
+    /// Sets `SRTO_MININPUTBW`, the floor in bytes per second for the input
+    /// rate estimate used when `SRTO_INPUTBW` is 0. Requires libsrt 1.4.3.
+    pub fn min_input_bw(&mut self, bytes_per_sec: i64) -> Result<&mut Self, UnsupportedOption> {
+        self.set(SockOpt::MinInputBw, bytes_per_sec)
+    }
+
+    /// Applies options from a comma-separated `key=value` string, such as
+    /// `"latency=120,oheadbw=50,fec=cols:10,rows:5"`.
+    ///
+    /// Keys are the option names used in SRT URIs, with values in the units
+    /// libsrt documents for them. `fec=<config>` is shorthand for
+    /// `packetfilter=fec,<config>`. Because packet filter configurations,
+    /// stream IDs and passphrases may contain commas themselves, a segment
+    /// that does not start with a known `key=` continues the preceding
+    /// value of one of those options, so `streamid=#!::r=live,m=publish`
+    /// sets the whole stream ID. Stream IDs and passphrases are taken
+    /// verbatim, whitespace included, while other keys and values are
+    /// trimmed. Blank segments, such as one after a trailing comma, are
+    /// skipped. Nothing is applied if any pair fails to parse.
+    pub fn apply_options(&mut self, options: &str) -> Result<&mut Self, ParseOptionError> {
+        let mut parsed: Vec<(SockOpt, OptionValue)> = Vec::new();
+        for raw in options.split(',') {
+            let segment = raw.trim();
+            let pair = raw.split_once('=').and_then(|(key, value)| {
+                let key = key.trim();
+                let verbatim = matches!(
+                    SockOpt::from_name(key),
+                    Some(SockOpt::StreamId | SockOpt::Passphrase)
+                );
+                let known = key.eq_ignore_ascii_case("fec") || SockOpt::from_name(key).is_some();
+                known.then(|| (key, if verbatim { value } else { value.trim() }))
+            });
+            let Some((key, value)) = pair else {
+                if segment.is_empty() {
+                    continue;
+                }
+                match parsed.last_mut() {
+                    Some((SockOpt::PacketFilter, OptionValue::Str(filter))) => {
+                        filter.push(',');
+                        filter.push_str(segment);
+                        continue;
+                    }
+                    Some((SockOpt::StreamId | SockOpt::Passphrase, OptionValue::Str(value))) => {
+                        value.push(',');
+                        value.push_str(raw);
+                        continue;
+                    }
+                    _ => {}
+                }
+                return Err(match segment.split_once('=') {
+                    Some((key, _)) => ParseOptionError::UnknownOption(key.trim().to_owned()),
+                    None => ParseOptionError::MissingValue(segment.to_owned()),
+                });
+            };
+            if key.eq_ignore_ascii_case("fec") {
+                parsed.push((SockOpt::PacketFilter, format!("fec,{value}").into()));
+                continue;
+            }
+            let option = SockOpt::from_name(key).expect("known option");
+            let kind = option.kind().expect("named options have a kind");
+            parsed.push((option, parse_value(option, kind, value)?));
+        }
+
+        let mut staged = self.clone();
+        for (option, value) in parsed {
+            if let Err(err) = staged.set(option, value) {
+                // Keep the record of the failed attempt, but not of the
+                // changes before it that are being rolled back.
+                let failed = staged.audit.and_then(|mut audit| audit.log.pop());
+                if let (Some(audit), Some(failed)) = (&mut self.audit, failed) {
+                    audit.log.push(failed);
+                }
+                return Err(err.into());
+            }
+        }
+        *self = staged;
+        Ok(self)
+    }
+
+    /// Checks the set for values libsrt would reject, such as an out of
+    /// range latency, a passphrase of the wrong length or a packet filter
+    /// on a file mode socket.
+    pub fn validate(&self) -> Result<(), SrtConfigError> {
+        for &(option, min, max) in RANGES {
+            if let Some(&OptionValue::Int(value)) = self.get(option) {
+                if !(min..=max).contains(&value) {
+                    return Err(SrtConfigError::ValueOutOfRange {
+                        option,
+                        value,
+                        min,
+                        max,
+                    });
+                }
+            }
+        }
+        if let Some(OptionValue::Str(passphrase)) = self.get(SockOpt::Passphrase) {
+            // An empty passphrase disables encryption.
+            if !passphrase.is_empty() {
+                Passphrase::new(passphrase.as_str())?;
+            }
+        }
+        if let (Some(_), Some(OptionValue::Str(transtype))) = (
+            self.get(SockOpt::PacketFilter),
+            self.get(SockOpt::TransType),
+        ) {
+            if transtype.eq_ignore_ascii_case("file") {
+                return Err(SrtConfigError::IncompatibleOptions {
+                    a: SockOpt::PacketFilter,
+                    b: SockOpt::TransType,
+                });
+            }
+        }
+        if let (Some(&OptionValue::Int(payload)), Some(&OptionValue::Int(mss))) =
+            (self.get(SockOpt::PayloadSize), self.get(SockOpt::Mss))
+        {
+            // The IPv4, UDP and SRT headers take 44 bytes of each segment.
+            if payload > mss - 44 {
+                return Err(SrtConfigError::IncompatibleOptions {
+                    a: SockOpt::PayloadSize,
+                    b: SockOpt::Mss,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, option: SockOpt) -> Option<&OptionValue> {
+        self.options
+            .iter()
+            .find(|(opt, _)| *opt == option)
+            .map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (SockOpt, &OptionValue)> {
+        self.options.iter().map(|(opt, value)| (*opt, value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_round_trips_through_raw() {
+        let version = Version::from_raw(0x010503);
+        assert_eq!(version, Version::new(1, 5, 3));
+        assert_eq!(version.to_raw(), 0x010503);
+        assert_eq!(version.to_string(), "1.5.3");
+    }
+
+    #[test]
+    fn min_input_bw_is_version_gated() {
+        let mut old = OptionSet::for_version(Version::new(1, 4, 2));
+        let err = old.min_input_bw(1_000_000).unwrap_err();
+        assert_eq!(err.since, Version::new(1, 4, 3));
+
+        let mut new = OptionSet::for_version(Version::new(1, 4, 3));
+        new.set(SockOpt::InputBw, 0).unwrap();
+        new.min_input_bw(1_000_000).unwrap();
+        assert_eq!(
+            new.get(SockOpt::MinInputBw),
+            Some(&OptionValue::Int(1_000_000))
+        );
+    }
+
+    #[test]
+    fn applies_option_strings() {
+        let mut options = OptionSet::new();
+        options
+            .apply_options("latency=120, oheadbw=50,fec=cols:10,rows:5,tlpktdrop=yes")
+            .unwrap();
+        assert_eq!(options.get(SockOpt::Latency), Some(&OptionValue::Int(120)));
+        assert_eq!(options.get(SockOpt::OheadBw), Some(&OptionValue::Int(50)));
+        assert_eq!(
+            options.get(SockOpt::PacketFilter),
+            Some(&OptionValue::from("fec,cols:10,rows:5"))
+        );
+        assert_eq!(
+            options.get(SockOpt::TlPktDrop),
+            Some(&OptionValue::Bool(true))
+        );
+    }
+
+    #[test]
+    fn continues_values_containing_commas() {
+        let mut options = OptionSet::new();
+        options
+            .apply_options("streamid=#!::r=live,m=publish,latency=200")
+            .unwrap();
+        assert_eq!(
+            options.get(SockOpt::StreamId),
+            Some(&OptionValue::from("#!::r=live,m=publish"))
+        );
+        assert_eq!(options.get(SockOpt::Latency), Some(&OptionValue::Int(200)));
+
+        options
+            .apply_options("passphrase= one, two,three ,")
+            .unwrap();
+        assert_eq!(
+            options.get(SockOpt::Passphrase),
+            Some(&OptionValue::from(" one, two,three "))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_option_strings_atomically() {
+        let mut options = OptionSet::new();
+        let err = options.apply_options("latency=120,bogus=1").unwrap_err();
+        assert_eq!(err, ParseOptionError::UnknownOption("bogus".into()));
+        assert!(options.is_empty());
+
+        let err = options.apply_options("latency=soon").unwrap_err();
+        assert!(matches!(err, ParseOptionError::InvalidValue { .. }));
+        assert!(options.apply_options("latency").is_err());
+    }
+
+    #[test]
+    fn audit_records_changes_by_source() {
+        let mut options = OptionSet::for_version(Version::new(1, 4, 0));
+        options.audit_source("defaults");
+        options.apply_options("latency=120").unwrap();
+        options.audit_source("stream.conf");
+        options.apply_options("latency=300").unwrap();
+        assert!(options.min_input_bw(1000).is_err());
+
+        let log = options.audit_log();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[1].source.as_deref(), Some("stream.conf"));
+        assert_eq!(log[1].old, Some(AuditedValue::Value(OptionValue::Int(120))));
+        assert_eq!(log[1].new, AuditedValue::Value(OptionValue::Int(300)));
+        assert!(log[2].result.is_err());
+
+        // A failed batch leaves only the failing attempt in the log.
+        assert!(options
+            .apply_options("latency=200,mininputbw=1000")
+            .is_err());
+        assert_eq!(options.audit_log().len(), 4);
+        assert_eq!(options.audit_log()[3].option, SockOpt::MinInputBw);
+        assert_eq!(options.get(SockOpt::Latency), Some(&OptionValue::Int(300)));
+        assert_eq!(options, {
+            let mut plain = OptionSet::for_version(Version::new(1, 4, 0));
+            plain.set(SockOpt::Latency, 300).unwrap();
+            plain
+        });
+    }
+
+    #[test]
+    fn audit_redacts_passphrases() {
+        let mut options = OptionSet::new();
+        options.enable_audit();
+        options.set(SockOpt::Passphrase, "first-secret").unwrap();
+        options.set(SockOpt::Passphrase, "second-secret").unwrap();
+
+        let change = &options.audit_log()[1];
+        assert_eq!(change.old, Some(AuditedValue::Redacted));
+        assert_eq!(change.new, AuditedValue::Redacted);
+        let debug = format!("{options:?}");
+        assert!(!debug.contains("secret"), "{debug}");
+    }
+
+    #[test]
+    fn low_latency_preset_is_valid() {
+        let options = OptionSet::low_latency_messages(40);
+        assert_eq!(options.validate(), Ok(()));
+        assert_eq!(options.get(SockOpt::Latency), Some(&OptionValue::Int(40)));
+        assert_eq!(
+            options.get(SockOpt::SndDropDelay),
+            Some(&OptionValue::Int(0))
+        );
+    }
+
+    #[test]
+    fn validate_reports_config_errors() {
+        let mut options = OptionSet::new();
+        options
+            .apply_options("latency=120,mss=1500,payloadsize=1456")
+            .unwrap();
+        assert_eq!(options.validate(), Ok(()));
+
+        options.set(SockOpt::Passphrase, "short").unwrap();
+        assert_eq!(
+            options.validate(),
+            Err(SrtConfigError::InvalidPassphraseLength(5))
+        );
+        options.set(SockOpt::Passphrase, "").unwrap();
+
+        options.set(SockOpt::Mss, 1000).unwrap();
+        assert_eq!(
+            options.validate(),
+            Err(SrtConfigError::IncompatibleOptions {
+                a: SockOpt::PayloadSize,
+                b: SockOpt::Mss,
+            })
+        );
+
+        options.set(SockOpt::Latency, -1).unwrap();
+        assert!(matches!(
+            options.validate(),
+            Err(SrtConfigError::ValueOutOfRange {
+                option: SockOpt::Latency,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn trans_type_defaults() {
+        let live = TransType::Live.defaults();
+        assert_eq!(live.get(SockOpt::TransType), Some(&"live".into()));
+        assert_eq!(live.get(SockOpt::PeerLatency), Some(&OptionValue::Int(120)));
+        assert_eq!(live.get(SockOpt::TlPktDrop), Some(&OptionValue::Bool(true)));
+
+        let file = TransType::File.defaults();
+        assert_eq!(file.len(), live.len());
+        assert_eq!(
+            file.get(SockOpt::MessageApi),
+            Some(&OptionValue::Bool(false))
+        );
+        assert_eq!(file.get(SockOpt::Congestion), Some(&"file".into()));
+    }
+}
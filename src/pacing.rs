@@ -0,0 +1,93 @@
+//! Receiver-side output pacing to the TSBPD schedule.
+//!
+//! libsrt hands a message to the application as soon as its TSBPD play time
+//! has come, but an application that drains its queue in bursts, or reads
+//! with a non-blocking socket, loses that timing. A [`Pacer`] restores it by
+//! holding each message back until its play time.
+//!
+//! [`SrtTransport`](crate::transport::SrtTransport) does not carry each
+//! message's `srctime`, so there is no paced receive mode built on it; await
+//! [`Pacer::wait`] with the `srctime` from `SRT_MSGCTRL` after each receive.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::timer::{self, Delay};
+
+/// Computes play times for the messages of one connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pacer {
+    connected_at: Instant,
+    latency: Duration,
+}
+
+impl Pacer {
+    /// Creates a pacer for a connection established at `connected_at`
+    /// (`srt_connection_time`) with the negotiated receiver latency.
+    pub fn new(connected_at: Instant, latency: Duration) -> Self {
+        Self {
+            connected_at,
+            latency,
+        }
+    }
+
+    /// When a message stamped `srctime` after the connection was established,
+    /// as taken from `SRT_MSGCTRL`, is due to be played, or `None` if a
+    /// misbehaving peer sent a stamp too large to represent.
+    pub fn play_time(&self, srctime: Duration) -> Option<Instant> {
+        self.connected_at
+            .checked_add(srctime)?
+            .checked_add(self.latency)
+    }
+
+    /// Completes at the message's play time, or at once if it is already
+    /// due or has no play time.
+    pub fn wait(&self, srctime: Duration) -> Delay {
+        timer::sleep_until(self.play_time(srctime).unwrap_or_else(Instant::now))
+    }
+
+    /// Blocks the current thread until the message's play time, if it has
+    /// one.
+    pub fn wait_blocking(&self, srctime: Duration) {
+        let Some(due) = self.play_time(srctime) else {
+            return;
+        };
+        let now = Instant::now();
+        if due > now {
+            thread::sleep(due - now);
+        }
+    }
+
+    /// How long past its play time the message is at `now`, if it is late
+    /// at all.
+    pub fn lateness(&self, srctime: Duration, now: Instant) -> Option<Duration> {
+        now.checked_duration_since(self.play_time(srctime)?)
+            .filter(|late| !late.is_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::tests::block_on;
+
+    #[test]
+    fn waits_until_play_time() {
+        let start = Instant::now();
+        let pacer = Pacer::new(start, Duration::from_millis(20));
+        assert_eq!(
+            pacer.play_time(Duration::from_millis(5)),
+            Some(start + Duration::from_millis(25))
+        );
+        assert_eq!(pacer.play_time(Duration::MAX), None);
+        block_on(pacer.wait(Duration::MAX));
+
+        block_on(pacer.wait(Duration::from_millis(5)));
+        assert!(start.elapsed() >= Duration::from_millis(25));
+        assert_eq!(pacer.lateness(Duration::from_secs(1), Instant::now()), None);
+        assert_eq!(
+            pacer.lateness(Duration::ZERO, start + Duration::from_millis(30)),
+            Some(Duration::from_millis(10))
+        );
+    }
+}
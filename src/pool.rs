@@ -0,0 +1,196 @@
+//! A pool of warm caller connections.
+
+use std::io;
+use std::net::SocketAddr;
+use std::ops::Deref;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::transport::SrtTransport;
+
+/// Sizing and expiry for a [`Pool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Idle connections kept open for reuse.
+    pub max_idle: usize,
+    /// Idle connections older than this are closed rather than reused.
+    pub idle_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_idle: 4,
+            idle_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+type HealthCheck<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// Keeps connections to a single remote open between short-lived exchanges,
+/// so each exchange skips the handshake.
+pub struct Pool<T> {
+    addr: SocketAddr,
+    config: PoolConfig,
+    idle: Mutex<Vec<(T, Instant)>>,
+    health_check: Option<HealthCheck<T>>,
+}
+
+impl<T: SrtTransport> Pool<T> {
+    pub fn new(addr: SocketAddr, config: PoolConfig) -> Self {
+        Self {
+            addr,
+            config,
+            idle: Mutex::new(Vec::new()),
+            health_check: None,
+        }
+    }
+
+    /// Runs `check` on idle connections before handing them out; those
+    /// failing it are closed and another is tried.
+    pub fn with_health_check<F>(mut self, check: F) -> Self
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        self.health_check = Some(Box::new(check));
+        self
+    }
+
+    /// Opens connections until `count` are idle, up to `max_idle`.
+    pub fn warm(&self, count: usize) -> io::Result<()> {
+        let target = count.min(self.config.max_idle);
+        while self.idle_count() < target {
+            let conn = T::connect(self.addr)?;
+            self.idle.lock().unwrap().push((conn, Instant::now()));
+        }
+        Ok(())
+    }
+
+    /// Checks out a connection, reusing the most recently returned healthy
+    /// one or connecting afresh.
+    pub fn get(&self) -> io::Result<Pooled<'_, T>> {
+        loop {
+            let Some((conn, returned)) = self.idle.lock().unwrap().pop() else {
+                break;
+            };
+            if returned.elapsed() >= self.config.idle_timeout {
+                continue;
+            }
+            if self
+                .health_check
+                .as_ref()
+                .is_some_and(|check| !check(&conn))
+            {
+                continue;
+            }
+            return Ok(self.checked_out(conn));
+        }
+        T::connect(self.addr).map(|conn| self.checked_out(conn))
+    }
+
+    /// Closes idle connections that have outlived the idle timeout.
+    pub fn prune(&self) {
+        let timeout = self.config.idle_timeout;
+        self.idle
+            .lock()
+            .unwrap()
+            .retain(|(_, returned)| returned.elapsed() < timeout);
+    }
+
+    pub fn idle_count(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+
+    fn checked_out(&self, conn: T) -> Pooled<'_, T> {
+        Pooled {
+            conn: Some(conn),
+            pool: self,
+        }
+    }
+
+    fn put_back(&self, conn: T) {
+        let mut idle = self.idle.lock().unwrap();
+        if idle.len() < self.config.max_idle {
+            idle.push((conn, Instant::now()));
+        }
+    }
+}
+
+/// A connection checked out of a [`Pool`], returned to it on drop.
+pub struct Pooled<'a, T: SrtTransport> {
+    conn: Option<T>,
+    pool: &'a Pool<T>,
+}
+
+impl<T: SrtTransport> Pooled<'_, T> {
+    /// Closes the connection instead of returning it to the pool, for use
+    /// after an error has left it in an unknown state.
+    pub fn discard(mut self) {
+        self.conn = None;
+    }
+}
+
+impl<T: SrtTransport> Deref for Pooled<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl<T: SrtTransport> Drop for Pooled<'_, T> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.put_back(conn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, UdpSocket};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn reuses_returned_connections() {
+        let server = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let pool: Pool<UdpSocket> = Pool::new(server.local_addr().unwrap(), PoolConfig::default());
+
+        let first = pool.get().unwrap().local_addr().unwrap();
+        assert_eq!(pool.idle_count(), 1);
+        let second = pool.get().unwrap().local_addr().unwrap();
+        assert_eq!(first, second);
+
+        pool.get().unwrap().discard();
+        assert_eq!(pool.idle_count(), 0);
+    }
+
+    #[test]
+    fn drops_unhealthy_and_expired_connections() {
+        let server = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let config = PoolConfig {
+            max_idle: 2,
+            idle_timeout: Duration::ZERO,
+        };
+        let pool: Pool<UdpSocket> = Pool::new(server.local_addr().unwrap(), config);
+        pool.warm(5).unwrap();
+        assert_eq!(pool.idle_count(), 2);
+        pool.prune();
+        assert_eq!(pool.idle_count(), 0);
+
+        let checks = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&checks);
+        let pool: Pool<UdpSocket> = Pool::new(server.local_addr().unwrap(), PoolConfig::default())
+            .with_health_check(move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                false
+            });
+        pool.warm(2).unwrap();
+        let _conn = pool.get().unwrap();
+        assert_eq!(checks.load(Ordering::Relaxed), 2);
+        assert_eq!(pool.idle_count(), 0);
+    }
+}
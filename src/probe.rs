@@ -0,0 +1,183 @@
+//! Ping-style round trip and one-way delay probes.
+//!
+//! The prober sends small timestamped request messages and the responder
+//! echoes each one back with the wall-clock time it was received, so a link
+//! can be qualified before real traffic starts. One-way figures compare the
+//! two hosts' wall clocks and are only meaningful if those are synchronised,
+//! for example with NTP or PTP.
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::transport::SrtTransport;
+
+const REQUEST: u8 = 0xf1;
+const REPLY: u8 = 0xf2;
+const REQUEST_LEN: usize = 13;
+const REPLY_LEN: usize = 21;
+
+/// The results of a [`ping`] run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProbeReport {
+    /// Requests sent.
+    pub sent: u32,
+    /// Round trip time of each answered request.
+    pub rtts: Vec<Duration>,
+    /// Sender-to-responder delay of each answered request, by wall clock.
+    /// Requests whose responder timestamp precedes the send time, as happens
+    /// with unsynchronised clocks, are left out.
+    pub one_way: Vec<Duration>,
+}
+
+impl ProbeReport {
+    pub fn received(&self) -> u32 {
+        self.rtts.len() as u32
+    }
+
+    /// Fraction of requests that went unanswered.
+    pub fn loss(&self) -> f64 {
+        if self.sent == 0 {
+            return 0.0;
+        }
+        1.0 - self.received() as f64 / self.sent as f64
+    }
+
+    pub fn rtt_min(&self) -> Option<Duration> {
+        self.rtts.iter().min().copied()
+    }
+
+    pub fn rtt_max(&self) -> Option<Duration> {
+        self.rtts.iter().max().copied()
+    }
+
+    pub fn rtt_avg(&self) -> Option<Duration> {
+        average(&self.rtts)
+    }
+
+    pub fn one_way_avg(&self) -> Option<Duration> {
+        average(&self.one_way)
+    }
+}
+
+fn average(samples: &[Duration]) -> Option<Duration> {
+    let count = u32::try_from(samples.len()).ok().filter(|&n| n > 0)?;
+    Some(samples.iter().sum::<Duration>() / count)
+}
+
+fn wall_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+/// Sends `count` probes `interval` apart, waiting up to `timeout` for each
+/// reply. The peer must be running [`respond`] or [`serve`].
+///
+/// Messages that are not replies to the outstanding probe are discarded.
+pub fn ping<T: SrtTransport + ?Sized>(
+    transport: &T,
+    count: u32,
+    interval: Duration,
+    timeout: Duration,
+) -> io::Result<ProbeReport> {
+    let mut report = ProbeReport::default();
+    let mut buf = [0; 64];
+    for seq in 0..count {
+        if seq > 0 {
+            thread::sleep(interval);
+        }
+        let sent_wall = wall_micros();
+        let mut request = [0; REQUEST_LEN];
+        request[0] = REQUEST;
+        request[1..5].copy_from_slice(&seq.to_be_bytes());
+        request[5..13].copy_from_slice(&sent_wall.to_be_bytes());
+        let sent_at = Instant::now();
+        transport.send(&request)?;
+        report.sent += 1;
+
+        let deadline = sent_at + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            let n = match transport.recv_timeout(&mut buf, Some(remaining)) {
+                Ok(0) => return Ok(report),
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => break,
+                Err(e) => return Err(e),
+            };
+            if n != REPLY_LEN || buf[0] != REPLY || buf[1..5] != seq.to_be_bytes() {
+                continue;
+            }
+            report.rtts.push(sent_at.elapsed());
+            let received_wall = u64::from_be_bytes(buf[13..21].try_into().unwrap());
+            if let Some(delay) = received_wall.checked_sub(sent_wall) {
+                report.one_way.push(Duration::from_micros(delay));
+            }
+            break;
+        }
+    }
+    Ok(report)
+}
+
+/// Answers `message` if it is a probe request, returning whether it was one.
+///
+/// Call this from an existing receive loop to make a connection probeable
+/// without dedicating it to [`serve`].
+pub fn respond<T: SrtTransport + ?Sized>(transport: &T, message: &[u8]) -> io::Result<bool> {
+    if message.len() != REQUEST_LEN || message[0] != REQUEST {
+        return Ok(false);
+    }
+    let mut reply = [0; REPLY_LEN];
+    reply[0] = REPLY;
+    reply[1..13].copy_from_slice(&message[1..13]);
+    reply[13..21].copy_from_slice(&wall_micros().to_be_bytes());
+    transport.send(&reply)?;
+    Ok(true)
+}
+
+/// Answers probe requests on a dedicated connection until the peer closes.
+pub fn serve<T: SrtTransport + ?Sized>(transport: &T) -> io::Result<()> {
+    let mut buf = [0; 64];
+    loop {
+        match transport.recv(&mut buf)? {
+            0 => return Ok(()),
+            n => {
+                respond(transport, &buf[..n])?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockConfig, MockTransport};
+
+    #[test]
+    fn measures_round_trips() {
+        let (prober, responder) = MockTransport::pair(MockConfig::default());
+
+        let report = thread::scope(|scope| {
+            scope.spawn(|| serve(&responder));
+            let report = ping(&prober, 3, Duration::from_millis(1), Duration::from_secs(1));
+            prober.send(b"").unwrap();
+            report.unwrap()
+        });
+
+        assert_eq!(report.sent, 3);
+        assert_eq!(report.received(), 3);
+        assert_eq!(report.loss(), 0.0);
+        assert!(report.rtt_min() <= report.rtt_avg());
+        assert!(report.rtt_avg() <= report.rtt_max());
+    }
+
+    #[test]
+    fn ignores_non_probe_messages() {
+        let (socket, _peer) = MockTransport::pair(MockConfig::default());
+        assert!(!respond(&socket, b"payload").unwrap());
+    }
+}
@@ -0,0 +1,184 @@
+//! Per-StreamID option profiles for listeners.
+
+use crate::crypto::{Passphrase, PassphraseProvider, PassphraseRequest};
+use crate::options::{OptionSet, OptionValue, SockOpt, SrtConfigError};
+use crate::reject::Reject;
+
+/// Maps StreamID patterns to the options applied to matching connections.
+///
+/// Patterns are globs where `*` matches any run of characters and `?` any
+/// single character; everything else matches literally. Rules are consulted
+/// in insertion order and the first match wins, falling back to the default
+/// profile if one is set.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileTable {
+    rules: Vec<(String, OptionSet)>,
+    default: Option<OptionSet>,
+}
+
+impl ProfileTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies `options` to connections whose StreamID matches `pattern`.
+    ///
+    /// Fails if the options are invalid, such as a passphrase of the wrong
+    /// length, so a misconfigured tenant is caught before any connection.
+    pub fn add(
+        &mut self,
+        pattern: impl Into<String>,
+        options: OptionSet,
+    ) -> Result<&mut Self, SrtConfigError> {
+        options.validate()?;
+        self.rules.push((pattern.into(), options));
+        Ok(self)
+    }
+
+    /// Applies `options` to connections that match no pattern.
+    pub fn set_default(&mut self, options: OptionSet) -> Result<&mut Self, SrtConfigError> {
+        options.validate()?;
+        self.default = Some(options);
+        Ok(self)
+    }
+
+    /// Returns the profile for a connection announcing `stream_id`.
+    pub fn lookup(&self, stream_id: &str) -> Option<&OptionSet> {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| glob_matches(pattern, stream_id))
+            .map(|(_, options)| options)
+            .or(self.default.as_ref())
+    }
+}
+
+/// Selects each pending connection's passphrase from the `SRTO_PASSPHRASE`
+/// of its profile, giving every tenant its own key on a single port.
+///
+/// A connection without a StreamID is looked up as the empty string.
+/// Profiles without a passphrase, or with an empty one, leave the connection
+/// unencrypted. A passphrase that is not valid rejects the connection rather
+/// than letting it through unencrypted.
+impl PassphraseProvider for ProfileTable {
+    fn passphrase(&self, request: &PassphraseRequest<'_>) -> Result<Option<Passphrase>, Reject> {
+        let Some(value) = self
+            .lookup(request.stream_id.unwrap_or(""))
+            .and_then(|options| options.get(SockOpt::Passphrase))
+        else {
+            return Ok(None);
+        };
+        match value {
+            OptionValue::Str(passphrase) if passphrase.is_empty() => Ok(None),
+            OptionValue::Str(passphrase) => Passphrase::new(passphrase.as_str())
+                .map(Some)
+                .map_err(|_| Reject::internal_error()),
+            _ => Err(Reject::internal_error()),
+        }
+    }
+}
+
+/// Extracts the user name (`u`) from a StreamID in the SRT access control
+/// syntax, such as `#!::r=live/cam1,u=alice`, for per-user passphrase
+/// lookups.
+pub fn stream_id_user(stream_id: &str) -> Option<&str> {
+    stream_id
+        .strip_prefix("#!::")?
+        .split(',')
+        .find_map(|pair| pair.strip_prefix("u="))
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latency(ms: i64) -> OptionSet {
+        let mut options = OptionSet::new();
+        options.set(SockOpt::Latency, ms).unwrap();
+        options
+    }
+
+    #[test]
+    fn globs_match() {
+        assert!(glob_matches("tenant-*/live", "tenant-42/live"));
+        assert!(glob_matches("cam-??", "cam-07"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("cam-??", "cam-7"));
+        assert!(!glob_matches("tenant-*/live", "tenant-42/vod"));
+    }
+
+    #[test]
+    fn first_matching_profile_wins() {
+        let mut table = ProfileTable::new();
+        table
+            .add("contrib/*", latency(500))
+            .and_then(|table| table.add("*", latency(200)))
+            .and_then(|table| table.set_default(latency(120)))
+            .unwrap();
+
+        let latency_of = |id| table.lookup(id).unwrap().get(SockOpt::Latency).cloned();
+        assert_eq!(latency_of("contrib/cam1"), Some(OptionValue::Int(500)));
+        assert_eq!(latency_of("other"), Some(OptionValue::Int(200)));
+    }
+
+    #[test]
+    fn selects_passphrase_by_stream_id() {
+        let mut tenant = OptionSet::new();
+        tenant.set(SockOpt::Passphrase, "tenant-a-secret").unwrap();
+        let mut table = ProfileTable::new();
+        table
+            .add("tenant-a/*", tenant)
+            .and_then(|table| table.set_default(latency(120)))
+            .unwrap();
+
+        let request = |stream_id| PassphraseRequest {
+            stream_id,
+            peer: "192.0.2.1:9000".parse().unwrap(),
+        };
+        let passphrase = table.passphrase(&request(Some("tenant-a/cam1"))).unwrap();
+        assert_eq!(passphrase.unwrap().as_str(), "tenant-a-secret");
+        assert_eq!(table.passphrase(&request(Some("tenant-b/cam1"))), Ok(None));
+        assert_eq!(table.passphrase(&request(None)), Ok(None));
+
+        let mut short = OptionSet::new();
+        short.set(SockOpt::Passphrase, "short").unwrap();
+        assert!(table.add("tenant-c/*", short).is_err());
+        let mut numeric = OptionSet::new();
+        numeric.set(SockOpt::Passphrase, 1234).unwrap();
+        table.add("tenant-d/*", numeric).unwrap();
+        assert_eq!(
+            table.passphrase(&request(Some("tenant-d/cam1"))),
+            Err(Reject::internal_error())
+        );
+
+        assert_eq!(stream_id_user("#!::r=live/cam1,u=alice"), Some("alice"));
+        assert_eq!(stream_id_user("live/cam1"), None);
+    }
+}
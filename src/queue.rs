@@ -0,0 +1,243 @@
+//! A bounded blocking queue with configurable overflow behaviour.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Condvar, Mutex};
+
+/// What to do when a message arrives at a full queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait for space, propagating backpressure to the producer.
+    #[default]
+    Block,
+    /// Discard the incoming message.
+    DropNewest,
+    /// Discard the oldest queued message to make room, so that a stalled
+    /// consumer sees the freshest data once it catches up.
+    DropOldest,
+}
+
+/// The class of a queued message. [`High`](Priority::High) messages are
+/// queued ahead of all [`Normal`](Priority::Normal) ones, keeping their
+/// relative order, so urgent traffic such as audio or control messages is
+/// sent first when the link is constrained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    #[default]
+    Normal,
+    High,
+}
+
+/// Error returned when pushing to a closed queue, carrying the rejected item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Closed<T>(pub T);
+
+impl<T> fmt::Display for Closed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("queue is closed")
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for Closed<T> {}
+
+#[derive(Debug)]
+struct State<T> {
+    items: VecDeque<T>,
+    /// The number of high priority items, all at the front of `items`.
+    high: usize,
+    closed: bool,
+    dropped: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct BoundedQueue<T> {
+    capacity: usize,
+    policy: OverflowPolicy,
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl<T> BoundedQueue<T> {
+    pub(crate) fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        assert!(capacity > 0, "queue capacity must be non-zero");
+        Self {
+            capacity,
+            policy,
+            state: Mutex::new(State {
+                items: VecDeque::with_capacity(capacity),
+                high: 0,
+                closed: false,
+                dropped: 0,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    /// Pushes `item`, applying the overflow policy if the queue is full.
+    pub(crate) fn push(&self, item: T) -> Result<(), Closed<T>> {
+        self.push_with_priority(item, Priority::Normal)
+    }
+
+    /// Pushes `item` behind queued items of the same or higher priority.
+    ///
+    /// When the queue is full, [`OverflowPolicy::DropOldest`] discards the
+    /// oldest normal priority item, falling back to the oldest high priority
+    /// one only if no normal items are queued. [`OverflowPolicy::DropNewest`]
+    /// makes room for a high priority item by discarding the newest normal
+    /// one, and discards the item itself only if no normal items are queued.
+    pub(crate) fn push_with_priority(&self, item: T, priority: Priority) -> Result<(), Closed<T>> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.closed {
+                return Err(Closed(item));
+            }
+            if state.items.len() < self.capacity {
+                break;
+            }
+            match self.policy {
+                OverflowPolicy::Block => state = self.not_full.wait(state).unwrap(),
+                OverflowPolicy::DropNewest => {
+                    state.dropped += 1;
+                    if priority == Priority::Normal || state.high == state.items.len() {
+                        return Ok(());
+                    }
+                    state.items.pop_back();
+                }
+                OverflowPolicy::DropOldest => {
+                    let high = state.high;
+                    if high < state.items.len() {
+                        state.items.remove(high);
+                    } else {
+                        state.items.pop_front();
+                        state.high -= 1;
+                    }
+                    state.dropped += 1;
+                }
+            }
+        }
+        match priority {
+            Priority::Normal => state.items.push_back(item),
+            Priority::High => {
+                let high = state.high;
+                state.items.insert(high, item);
+                state.high += 1;
+            }
+        }
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// Pops the oldest item, waiting for one to arrive. Returns `None` once
+    /// the queue is closed and drained.
+    pub(crate) fn pop(&self) -> Option<T> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(item) = pop_front(&mut state) {
+                self.not_full.notify_one();
+                return Some(item);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self.not_empty.wait(state).unwrap();
+        }
+    }
+
+    /// Pops the oldest item if one is immediately available.
+    pub(crate) fn try_pop(&self) -> Option<T> {
+        let item = pop_front(&mut self.state.lock().unwrap());
+        if item.is_some() {
+            self.not_full.notify_one();
+        }
+        item
+    }
+
+    /// Rejects further pushes and wakes all waiters. Queued items remain
+    /// available to `pop`.
+    pub(crate) fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    /// Items discarded by the overflow policy so far.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+}
+
+fn pop_front<T>(state: &mut State<T>) -> Option<T> {
+    let item = state.items.pop_front()?;
+    state.high = state.high.saturating_sub(1);
+    Some(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_oldest_keeps_latest_items() {
+        let queue = BoundedQueue::new(2, OverflowPolicy::DropOldest);
+        for i in 0..5 {
+            queue.push(i).unwrap();
+        }
+        assert_eq!(queue.dropped(), 3);
+        assert_eq!(queue.try_pop(), Some(3));
+        assert_eq!(queue.try_pop(), Some(4));
+    }
+
+    #[test]
+    fn drop_newest_keeps_earliest_items() {
+        let queue = BoundedQueue::new(2, OverflowPolicy::DropNewest);
+        for i in 0..5 {
+            queue.push(i).unwrap();
+        }
+        assert_eq!(queue.dropped(), 3);
+        assert_eq!(queue.try_pop(), Some(0));
+        assert_eq!(queue.try_pop(), Some(1));
+    }
+
+    #[test]
+    fn high_priority_jumps_ahead() {
+        let queue = BoundedQueue::new(3, OverflowPolicy::DropOldest);
+        queue.push(1).unwrap();
+        queue.push(2).unwrap();
+        queue.push_with_priority(10, Priority::High).unwrap();
+        queue.push_with_priority(11, Priority::High).unwrap();
+        assert_eq!(queue.dropped(), 1);
+        assert_eq!(queue.try_pop(), Some(10));
+        assert_eq!(queue.try_pop(), Some(11));
+        assert_eq!(queue.try_pop(), Some(2));
+    }
+
+    #[test]
+    fn drop_newest_makes_room_for_high_priority() {
+        let queue = BoundedQueue::new(2, OverflowPolicy::DropNewest);
+        queue.push(1).unwrap();
+        queue.push(2).unwrap();
+        queue.push_with_priority(10, Priority::High).unwrap();
+        queue.push_with_priority(11, Priority::High).unwrap();
+        queue.push_with_priority(12, Priority::High).unwrap();
+        assert_eq!(queue.dropped(), 3);
+        assert_eq!(queue.try_pop(), Some(10));
+        assert_eq!(queue.try_pop(), Some(11));
+        assert_eq!(queue.try_pop(), None);
+    }
+
+    #[test]
+    fn close_drains_then_ends() {
+        let queue = BoundedQueue::new(4, OverflowPolicy::Block);
+        queue.push(1).unwrap();
+        queue.close();
+        assert_eq!(queue.push(2), Err(Closed(2)));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), None);
+    }
+}
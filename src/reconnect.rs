@@ -0,0 +1,457 @@
+//! Automatic reconnection for caller-side transports.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::resolver::Resolver;
+use crate::transport::SrtTransport;
+
+/// Decides how long to wait between reconnection attempts, and when to stop.
+pub trait ReconnectPolicy {
+    /// Returns the delay to wait after failed attempt `attempt` (counting from
+    /// 1) before trying again, or `None` to give up.
+    fn next_delay(&mut self, attempt: u32) -> Option<Duration>;
+
+    /// Called once a connection has been established, so stateful policies
+    /// can start afresh for the next outage.
+    fn reset(&mut self) {}
+}
+
+impl<P: ReconnectPolicy + ?Sized> ReconnectPolicy for Box<P> {
+    fn next_delay(&mut self, attempt: u32) -> Option<Duration> {
+        (**self).next_delay(attempt)
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
+
+/// Retries at a constant interval.
+#[derive(Debug, Clone)]
+pub struct FixedInterval {
+    pub interval: Duration,
+    /// Attempts after which to give up, or `None` to retry forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl FixedInterval {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy for FixedInterval {
+    fn next_delay(&mut self, attempt: u32) -> Option<Duration> {
+        match self.max_attempts {
+            Some(max) if attempt >= max => None,
+            _ => Some(self.interval),
+        }
+    }
+}
+
+/// Retries with exponentially growing delays, optionally randomised.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    pub initial: Duration,
+    /// The longest delay, jitter included.
+    pub max: Duration,
+    /// Growth factor per attempt. A delay that a negative or NaN multiplier
+    /// makes invalid is replaced by `max`.
+    pub multiplier: f64,
+    /// Fraction of each delay to randomise by, from 0 (none) to 1 (the delay
+    /// may fall anywhere between zero and twice its nominal value).
+    pub jitter: f64,
+    /// Attempts after which to give up, or `None` to retry forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy for ExponentialBackoff {
+    fn next_delay(&mut self, attempt: u32) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|max| attempt >= max) {
+            return None;
+        }
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let nominal = (self.initial.as_secs_f64() * self.multiplier.powi(exponent))
+            .min(self.max.as_secs_f64());
+        let jitter = self.jitter.clamp(0.0, 1.0) * (2.0 * random_unit() - 1.0);
+        let delay = Duration::try_from_secs_f64(nominal * (1.0 + jitter)).unwrap_or(self.max);
+        Some(delay.min(self.max))
+    }
+}
+
+/// A uniformly distributed value in `[0, 1)`, seeded from std's per-process
+/// random hasher keys.
+fn random_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Where a [`Reconnecting`] transport connects to.
+///
+/// A target built from a hostname is re-resolved on reconnect attempts once
+/// its refresh interval has passed, so DNS-based failover takes effect. A
+/// target built from a [`Resolver`] asks it on every attempt, leaving
+/// caching to the resolver. If resolution fails, the previously resolved
+/// addresses are reused.
+#[derive(Debug, Clone)]
+pub struct Target {
+    source: Source,
+    refresh: Duration,
+    addrs: Vec<SocketAddr>,
+    resolved_at: Option<Instant>,
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Fixed,
+    Host(String),
+    Resolver(Resolver, String),
+}
+
+impl Target {
+    /// A target named by `host:port`, re-resolved on every attempt.
+    pub fn host(host_port: impl Into<String>) -> Self {
+        Self::with_source(Source::Host(host_port.into()), Vec::new())
+    }
+
+    /// A target named by a connection string that `resolver` resolves and
+    /// ranks on every attempt.
+    pub fn resolver(resolver: Resolver, connection: impl Into<String>) -> Self {
+        Self::with_source(Source::Resolver(resolver, connection.into()), Vec::new())
+    }
+
+    fn with_source(source: Source, addrs: Vec<SocketAddr>) -> Self {
+        Self {
+            source,
+            refresh: Duration::ZERO,
+            addrs,
+            resolved_at: None,
+        }
+    }
+
+    /// Reuses resolved addresses for `refresh` before resolving again.
+    pub fn with_refresh(mut self, refresh: Duration) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Returns the candidate addresses, resolving the target if the cached
+    /// result is older than the refresh interval.
+    pub fn resolve(&mut self) -> io::Result<&[SocketAddr]> {
+        let fresh = self
+            .resolved_at
+            .is_some_and(|at| at.elapsed() < self.refresh);
+        if fresh {
+            return Ok(&self.addrs);
+        }
+        let resolved = match &self.source {
+            Source::Fixed => return Ok(&self.addrs),
+            Source::Host(host) => host.to_socket_addrs().map(Iterator::collect),
+            Source::Resolver(resolver, connection) => resolver
+                .resolve(connection)
+                .map(|candidates| candidates.iter().map(|c| c.addr).collect()),
+        };
+        match resolved {
+            Ok(addrs) => {
+                self.addrs = addrs;
+                self.resolved_at = Some(Instant::now());
+            }
+            Err(e) if self.addrs.is_empty() => return Err(e),
+            Err(_) => {}
+        }
+        Ok(&self.addrs)
+    }
+}
+
+impl From<SocketAddr> for Target {
+    fn from(addr: SocketAddr) -> Self {
+        Self::with_source(Source::Fixed, vec![addr])
+    }
+}
+
+impl From<&str> for Target {
+    fn from(host_port: &str) -> Self {
+        Target::host(host_port)
+    }
+}
+
+impl From<String> for Target {
+    fn from(host_port: String) -> Self {
+        Target::host(host_port)
+    }
+}
+
+/// A caller-side transport that transparently reconnects when the link fails.
+///
+/// Timeouts are passed through to the caller untouched; any other error, or
+/// the peer closing the connection, triggers a reconnect governed by the
+/// policy `P`.
+pub struct Reconnecting<T, P> {
+    target: Target,
+    policy: P,
+    transport: Option<T>,
+    generation: u64,
+    metrics: ReconnectMetrics,
+}
+
+/// Upper bounds of the [`ReconnectMetrics`] time-to-reconnect histogram
+/// buckets. A final bucket counts reconnections slower than the last bound.
+pub const RECONNECT_TIME_BUCKETS: [Duration; 8] = [
+    Duration::from_millis(10),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+];
+
+/// Counters and timings describing how often, and how quickly, a
+/// [`Reconnecting`] transport has reconnected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconnectMetrics {
+    /// Connection attempts made, including the initial connection.
+    pub attempts: u64,
+    /// Attempts that failed.
+    pub failures: u64,
+    /// Outages after which a connection was re-established.
+    pub reconnects: u64,
+    /// Outages after which the policy gave up.
+    pub give_ups: u64,
+    /// Time taken by the most recent successful reconnection.
+    pub last_reconnect_time: Option<Duration>,
+    /// Total time spent reconnecting successfully.
+    pub total_reconnect_time: Duration,
+    /// Successful reconnections by duration, one count per bucket of
+    /// [`RECONNECT_TIME_BUCKETS`] plus one for slower reconnections.
+    pub reconnect_time_histogram: [u64; RECONNECT_TIME_BUCKETS.len() + 1],
+}
+
+impl ReconnectMetrics {
+    fn record_reconnect(&mut self, elapsed: Duration) {
+        self.reconnects += 1;
+        self.last_reconnect_time = Some(elapsed);
+        self.total_reconnect_time += elapsed;
+        let bucket = RECONNECT_TIME_BUCKETS
+            .iter()
+            .position(|&bound| elapsed <= bound)
+            .unwrap_or(RECONNECT_TIME_BUCKETS.len());
+        self.reconnect_time_histogram[bucket] += 1;
+    }
+}
+
+impl<T: SrtTransport, P: ReconnectPolicy> Reconnecting<T, P> {
+    /// Connects to `target`, retrying according to `policy` until it
+    /// succeeds or the policy gives up.
+    pub fn connect(target: impl Into<Target>, policy: P) -> io::Result<Self> {
+        let mut this = Self {
+            target: target.into(),
+            policy,
+            transport: None,
+            generation: 0,
+            metrics: ReconnectMetrics::default(),
+        };
+        this.reconnect()?;
+        Ok(this)
+    }
+
+    /// How many connections have been established, including the first.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Reconnection counters and timings accumulated so far.
+    pub fn metrics(&self) -> &ReconnectMetrics {
+        &self.metrics
+    }
+
+    /// The current underlying transport, if connected.
+    pub fn get_ref(&self) -> Option<&T> {
+        self.transport.as_ref()
+    }
+
+    /// Sends a message, reconnecting and retrying once if the link has failed.
+    pub fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(transport) = &self.transport {
+            match transport.send(buf) {
+                Err(e) if is_link_failure(&e) => {}
+                result => return result,
+            }
+        }
+        self.reconnect()?;
+        self.transport.as_ref().unwrap().send(buf)
+    }
+
+    /// Receives a message, reconnecting as needed until one arrives.
+    pub fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if let Some(transport) = &self.transport {
+                match transport.recv(buf) {
+                    Ok(0) => {}
+                    Err(e) if is_link_failure(&e) => {}
+                    result => return result,
+                }
+            }
+            self.reconnect()?;
+        }
+    }
+
+    fn reconnect(&mut self) -> io::Result<()> {
+        self.transport = None;
+        let started = Instant::now();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            self.metrics.attempts += 1;
+            match self.try_connect() {
+                Ok(transport) => {
+                    self.transport = Some(transport);
+                    if self.generation > 0 {
+                        self.metrics.record_reconnect(started.elapsed());
+                    }
+                    self.generation += 1;
+                    self.policy.reset();
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.metrics.failures += 1;
+                    match self.policy.next_delay(attempt) {
+                        Some(delay) => thread::sleep(delay),
+                        None => {
+                            self.metrics.give_ups += 1;
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn try_connect(&mut self) -> io::Result<T> {
+        let mut last_err = None;
+        for &addr in self.target.resolve()? {
+            match T::connect(addr) {
+                Ok(transport) => return Ok(transport),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "target resolved to no addresses")
+        }))
+    }
+}
+
+fn is_link_failure(err: &io::Error) -> bool {
+    !matches!(
+        err.kind(),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential_backoff_grows_and_caps() {
+        let mut policy = ExponentialBackoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_millis(500),
+            jitter: 0.0,
+            max_attempts: Some(5),
+            ..ExponentialBackoff::default()
+        };
+        let delays: Vec<_> = (1..=5).map(|n| policy.next_delay(n)).collect();
+        assert_eq!(
+            delays,
+            [
+                Some(Duration::from_millis(100)),
+                Some(Duration::from_millis(200)),
+                Some(Duration::from_millis(400)),
+                Some(Duration::from_millis(500)),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let mut policy = ExponentialBackoff {
+            jitter: 0.5,
+            ..ExponentialBackoff::default()
+        };
+        for _ in 0..100 {
+            let delay = policy.next_delay(1).unwrap();
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150));
+            assert!(policy.next_delay(20).unwrap() <= policy.max);
+        }
+    }
+
+    #[test]
+    fn invalid_multipliers_fall_back_to_max() {
+        for multiplier in [-2.0, f64::NAN] {
+            let mut policy = ExponentialBackoff {
+                multiplier,
+                jitter: 0.0,
+                ..ExponentialBackoff::default()
+            };
+            assert_eq!(policy.next_delay(2), Some(policy.max));
+        }
+    }
+
+    #[test]
+    fn target_caches_within_refresh_interval() {
+        let mut target = Target::host("localhost:9000").with_refresh(Duration::from_secs(60));
+        let first = target.resolve().unwrap().to_vec();
+        assert!(first.iter().all(|addr| addr.port() == 9000));
+        let resolved_at = target.resolved_at;
+        target.resolve().unwrap();
+        assert_eq!(target.resolved_at, resolved_at);
+    }
+
+    #[test]
+    fn fixed_interval_gives_up() {
+        let mut policy = FixedInterval {
+            interval: Duration::from_secs(1),
+            max_attempts: Some(2),
+        };
+        assert_eq!(policy.next_delay(1), Some(Duration::from_secs(1)));
+        assert_eq!(policy.next_delay(2), None);
+    }
+
+    #[test]
+    fn metrics_bucket_reconnect_times() {
+        let mut metrics = ReconnectMetrics::default();
+        metrics.record_reconnect(Duration::from_millis(5));
+        metrics.record_reconnect(Duration::from_millis(300));
+        metrics.record_reconnect(Duration::from_secs(60));
+        assert_eq!(metrics.reconnects, 3);
+        assert_eq!(metrics.last_reconnect_time, Some(Duration::from_secs(60)));
+        assert_eq!(
+            metrics.reconnect_time_histogram,
+            [1, 0, 0, 1, 0, 0, 0, 0, 1]
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Connection rejection reasons.
+
+use std::fmt;
+
+/// Why a connection was rejected, mirroring libsrt's `SRT_REJECT_REASON`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RejectReason {
+    Unknown,
+    System,
+    Peer,
+    Resource,
+    Rogue,
+    Backlog,
+    Ipe,
+    Close,
+    Version,
+    RdvCookie,
+    BadSecret,
+    Unsecure,
+    MessageApi,
+    Congestion,
+    Filter,
+    Group,
+    Timeout,
+    Crypto,
+}
+
+impl RejectReason {
+    const ALL: [RejectReason; 18] = [
+        RejectReason::Unknown,
+        RejectReason::System,
+        RejectReason::Peer,
+        RejectReason::Resource,
+        RejectReason::Rogue,
+        RejectReason::Backlog,
+        RejectReason::Ipe,
+        RejectReason::Close,
+        RejectReason::Version,
+        RejectReason::RdvCookie,
+        RejectReason::BadSecret,
+        RejectReason::Unsecure,
+        RejectReason::MessageApi,
+        RejectReason::Congestion,
+        RejectReason::Filter,
+        RejectReason::Group,
+        RejectReason::Timeout,
+        RejectReason::Crypto,
+    ];
+
+    /// The numeric `SRT_REJ_*` code.
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Maps a numeric reject code, falling back to `Unknown` for codes this
+    /// version of the crate does not recognise.
+    pub fn from_code(code: i32) -> Self {
+        usize::try_from(code)
+            .ok()
+            .and_then(|i| Self::ALL.get(i).copied())
+            .unwrap_or(RejectReason::Unknown)
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            RejectReason::Unknown => "Unknown or erroneous",
+            RejectReason::System => "Error in system calls",
+            RejectReason::Peer => "Peer rejected connection",
+            RejectReason::Resource => "Resource allocation failure",
+            RejectReason::Rogue => "Rogue peer or incorrect parameters",
+            RejectReason::Backlog => "Listener's backlog exceeded",
+            RejectReason::Ipe => "Internal Program Error",
+            RejectReason::Close => "Socket is being closed",
+            RejectReason::Version => "Peer version too old",
+            RejectReason::RdvCookie => "Rendezvous-mode cookie collision",
+            RejectReason::BadSecret => "Incorrect passphrase",
+            RejectReason::Unsecure => "Password required or unexpected",
+            RejectReason::MessageApi => "MessageAPI/StreamAPI collision",
+            RejectReason::Congestion => "Congestion controller type collision",
+            RejectReason::Filter => "Packet Filter settings error",
+            RejectReason::Group => "Group settings collision",
+            RejectReason::Timeout => "Connection timeout",
+            RejectReason::Crypto => "Crypto mode",
+        }
+    }
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+impl std::error::Error for RejectReason {}
+
+/// The first code of libsrt's predefined rejection space
+/// (`SRT_REJC_PREDEFINED`).
+pub const PREDEFINED_BASE: i32 = 1000;
+/// The first code left for applications to define (`SRT_REJC_USERDEFINED`).
+pub const USER_DEFINED_BASE: i32 = 2000;
+
+/// A rejection as sent by a listener callback and decoded by the caller.
+///
+/// Predefined codes follow libsrt's `SRT_REJX_*` values, most of which are
+/// [`PREDEFINED_BASE`] plus the matching HTTP status, so `unauthorized()` is
+/// 1401 and `not_found()` is 1404.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reject {
+    /// A rejection raised by libsrt itself.
+    Srt(RejectReason),
+    /// A code in the predefined space, as an offset from [`PREDEFINED_BASE`]
+    /// below 1000. See [`Reject::predefined`].
+    Predefined(u16),
+    /// An application-defined code, as a non-negative offset from
+    /// [`USER_DEFINED_BASE`]. See [`Reject::user`].
+    User(i32),
+}
+
+impl Reject {
+    /// A code in the predefined space, or `None` if `offset` would run into
+    /// the user-defined space.
+    pub fn predefined(offset: u16) -> Option<Self> {
+        let reject = Reject::Predefined(offset);
+        reject.in_range().then_some(reject)
+    }
+
+    /// An application-defined code, or `None` if `offset` is negative or
+    /// the code would overflow.
+    pub fn user(offset: i32) -> Option<Self> {
+        let reject = Reject::User(offset);
+        reject.in_range().then_some(reject)
+    }
+
+    fn in_range(self) -> bool {
+        match self {
+            Reject::Srt(_) => true,
+            Reject::Predefined(offset) => i32::from(offset) < USER_DEFINED_BASE - PREDEFINED_BASE,
+            Reject::User(offset) => (0..=i32::MAX - USER_DEFINED_BASE).contains(&offset),
+        }
+    }
+
+    /// The request was malformed (1400).
+    pub fn bad_request() -> Self {
+        Reject::Predefined(400)
+    }
+
+    /// Credentials were missing or incorrect (1401).
+    pub fn unauthorized() -> Self {
+        Reject::Predefined(401)
+    }
+
+    /// The listener is too busy to take the connection (1402).
+    pub fn overloaded() -> Self {
+        Reject::Predefined(402)
+    }
+
+    /// The caller may not access the requested resource (1403).
+    pub fn forbidden() -> Self {
+        Reject::Predefined(403)
+    }
+
+    /// The requested resource does not exist (1404).
+    pub fn not_found() -> Self {
+        Reject::Predefined(404)
+    }
+
+    /// The resource is already in use, such as a publisher already
+    /// streaming to it (1409).
+    pub fn conflict() -> Self {
+        Reject::Predefined(409)
+    }
+
+    /// The listener failed while handling the request (1500).
+    pub fn internal_error() -> Self {
+        Reject::Predefined(500)
+    }
+
+    /// The service is down, for example for maintenance (1503).
+    pub fn unavailable() -> Self {
+        Reject::Predefined(503)
+    }
+
+    /// The numeric code to pass to `srt_setrejectreason`.
+    ///
+    /// An offset outside its space, which the checked constructors refuse,
+    /// has no code of its own and encodes as [`RejectReason::Unknown`].
+    pub fn code(self) -> i32 {
+        match self {
+            _ if !self.in_range() => RejectReason::Unknown.code(),
+            Reject::Srt(reason) => reason.code(),
+            Reject::Predefined(offset) => PREDEFINED_BASE + i32::from(offset),
+            Reject::User(offset) => USER_DEFINED_BASE + offset,
+        }
+    }
+
+    /// Decodes a code returned by `srt_getrejectreason`.
+    pub fn from_code(code: i32) -> Self {
+        if code >= USER_DEFINED_BASE {
+            Reject::User(code - USER_DEFINED_BASE)
+        } else if code >= PREDEFINED_BASE {
+            Reject::Predefined((code - PREDEFINED_BASE) as u16)
+        } else {
+            Reject::Srt(RejectReason::from_code(code))
+        }
+    }
+
+    fn description(self) -> Option<&'static str> {
+        let description = match self {
+            Reject::Srt(reason) => reason.description(),
+            Reject::Predefined(0) => "Rejected by application",
+            Reject::Predefined(400) => "Bad request",
+            Reject::Predefined(401) => "Unauthorized",
+            Reject::Predefined(402) => "Server overloaded",
+            Reject::Predefined(403) => "Forbidden",
+            Reject::Predefined(404) => "Resource not found",
+            Reject::Predefined(409) => "Resource already in use",
+            Reject::Predefined(500) => "Internal server error",
+            Reject::Predefined(503) => "Service unavailable",
+            _ => return None,
+        };
+        Some(description)
+    }
+}
+
+impl From<RejectReason> for Reject {
+    fn from(reason: RejectReason) -> Self {
+        Reject::Srt(reason)
+    }
+}
+
+impl fmt::Display for Reject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.description() {
+            Some(description) => f.write_str(description),
+            None => write!(f, "Rejected with code {}", self.code()),
+        }
+    }
+}
+
+impl std::error::Error for Reject {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for reason in RejectReason::ALL {
+            assert_eq!(RejectReason::from_code(reason.code()), reason);
+        }
+        assert_eq!(RejectReason::from_code(-1), RejectReason::Unknown);
+        assert_eq!(RejectReason::Crypto.code(), 17);
+    }
+
+    #[test]
+    fn rejects_round_trip() {
+        assert_eq!(Reject::unauthorized().code(), 1401);
+        assert_eq!(Reject::not_found().code(), 1404);
+        for reject in [
+            Reject::overloaded(),
+            Reject::User(7),
+            Reject::predefined(999).unwrap(),
+            Reject::user(i32::MAX - USER_DEFINED_BASE).unwrap(),
+            Reject::Srt(RejectReason::BadSecret),
+        ] {
+            assert_eq!(Reject::from_code(reject.code()), reject);
+        }
+        assert_eq!(Reject::user(-5), None);
+        assert_eq!(Reject::predefined(1500), None);
+        for invalid in [Reject::User(-5), Reject::Predefined(1500)] {
+            assert_eq!(
+                Reject::from_code(invalid.code()),
+                Reject::Srt(RejectReason::Unknown)
+            );
+        }
+        assert_eq!(Reject::from_code(1403).to_string(), "Forbidden");
+        assert_eq!(Reject::User(7).to_string(), "Rejected with code 2007");
+    }
+}
@@ -0,0 +1,300 @@
+//! Shared resolution of connection strings into ranked addresses.
+//!
+//! A [`Resolver`] turns `srt://` URIs or `host:port` strings into candidate
+//! addresses, optionally probes each candidate, and caches the ranked result
+//! so every connection to the same service shares one lookup.
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::reconnect::Target;
+
+type Probe = Box<dyn Fn(SocketAddr) -> Option<Duration> + Send + Sync>;
+
+/// A resolved address and, if probed, its round trip time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub addr: SocketAddr,
+    /// `None` if the candidate was not probed or did not answer.
+    pub rtt: Option<Duration>,
+}
+
+struct Inner {
+    ttl: Duration,
+    probe: Option<Probe>,
+    cache: Mutex<HashMap<String, (Instant, Vec<Candidate>)>>,
+    in_flight: Mutex<HashMap<String, Vec<Arc<Mutex<Slot>>>>>,
+}
+
+/// Resolves connection strings, caching each ranked result for a TTL.
+///
+/// Clones share the cache. If a lookup fails, an expired cached result is
+/// reused rather than failing the caller.
+#[derive(Clone)]
+pub struct Resolver {
+    inner: Arc<Inner>,
+}
+
+impl Resolver {
+    /// Creates a resolver that reuses results for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self::build(ttl, None)
+    }
+
+    /// Creates a resolver that also measures each candidate with `probe`,
+    /// ranking those that answer by round trip time ahead of those that
+    /// do not. [`probe::ping`] over a fresh connection is a typical probe.
+    ///
+    /// [`probe::ping`]: crate::probe::ping
+    pub fn with_probe<F>(ttl: Duration, probe: F) -> Self
+    where
+        F: Fn(SocketAddr) -> Option<Duration> + Send + Sync + 'static,
+    {
+        Self::build(ttl, Some(Box::new(probe)))
+    }
+
+    fn build(ttl: Duration, probe: Option<Probe>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                ttl,
+                probe,
+                cache: Mutex::new(HashMap::new()),
+                in_flight: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Returns the ranked candidates for `connection`, blocking on DNS and
+    /// probes when the cached result has expired.
+    pub fn resolve(&self, connection: &str) -> io::Result<Vec<Candidate>> {
+        self.lookup(host_port(connection)?)
+    }
+
+    fn lookup(&self, host_port: &str) -> io::Result<Vec<Candidate>> {
+        let stale = match self.inner.cache.lock().unwrap().get(host_port) {
+            Some((at, candidates)) if at.elapsed() < self.inner.ttl => {
+                return Ok(candidates.clone())
+            }
+            Some((_, candidates)) => Some(candidates.clone()),
+            None => None,
+        };
+        let addrs = match (host_port.to_socket_addrs(), stale) {
+            (Ok(addrs), _) => addrs,
+            (Err(_), Some(stale)) => return Ok(stale),
+            (Err(e), None) => return Err(e),
+        };
+        let mut candidates: Vec<Candidate> = addrs
+            .map(|addr| Candidate {
+                addr,
+                rtt: self.inner.probe.as_ref().and_then(|probe| probe(addr)),
+            })
+            .collect();
+        if self.inner.probe.is_some() {
+            candidates.sort_by_key(|candidate| (candidate.rtt.is_none(), candidate.rtt));
+        }
+        self.inner
+            .cache
+            .lock()
+            .unwrap()
+            .insert(host_port.to_owned(), (Instant::now(), candidates.clone()));
+        Ok(candidates)
+    }
+
+    /// Resolves without blocking, completing with the same result as
+    /// [`resolve`](Resolver::resolve). Works under any async runtime.
+    ///
+    /// A fresh cached result completes at once. Otherwise the lookup runs
+    /// on a background thread, shared by every call for the same host made
+    /// while it is in flight. A panicking probe fails the lookup with
+    /// [`io::ErrorKind::Other`].
+    pub fn resolve_async(&self, connection: &str) -> Resolving {
+        let slot = Arc::new(Mutex::new(Slot::default()));
+        let resolving = Resolving {
+            slot: Arc::clone(&slot),
+        };
+        let host_port = match host_port(connection) {
+            Ok(host_port) => host_port.to_owned(),
+            Err(e) => {
+                slot.lock().unwrap().complete(Err(e));
+                return resolving;
+            }
+        };
+        if let Some((at, candidates)) = self.inner.cache.lock().unwrap().get(&host_port) {
+            if at.elapsed() < self.inner.ttl {
+                slot.lock().unwrap().complete(Ok(candidates.clone()));
+                return resolving;
+            }
+        }
+        match self
+            .inner
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(host_port.clone())
+        {
+            Entry::Occupied(mut waiting) => {
+                waiting.get_mut().push(slot);
+                return resolving;
+            }
+            Entry::Vacant(waiting) => {
+                waiting.insert(vec![slot]);
+            }
+        }
+        let resolver = self.clone();
+        thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| resolver.lookup(&host_port)))
+                .unwrap_or_else(|_| Err(io::Error::other("resolver probe panicked")));
+            let waiting = resolver.inner.in_flight.lock().unwrap().remove(&host_port);
+            for slot in waiting.into_iter().flatten() {
+                let result = match &result {
+                    Ok(candidates) => Ok(candidates.clone()),
+                    Err(e) => Err(io::Error::new(e.kind(), e.to_string())),
+                };
+                slot.lock().unwrap().complete(result);
+            }
+        });
+        resolving
+    }
+
+    /// A reconnect target that asks this resolver for ranked candidates on
+    /// every attempt, for use with
+    /// [`Reconnecting`](crate::reconnect::Reconnecting).
+    pub fn target(&self, connection: impl Into<String>) -> Target {
+        Target::resolver(self.clone(), connection)
+    }
+}
+
+impl fmt::Debug for Resolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Resolver")
+            .field("ttl", &self.inner.ttl)
+            .field("probe", &self.inner.probe.is_some())
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct Slot {
+    result: Option<io::Result<Vec<Candidate>>>,
+    waker: Option<Waker>,
+}
+
+impl Slot {
+    fn complete(&mut self, result: io::Result<Vec<Candidate>>) {
+        self.result = Some(result);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Future returned by [`Resolver::resolve_async`].
+#[must_use = "futures do nothing unless polled"]
+pub struct Resolving {
+    slot: Arc<Mutex<Slot>>,
+}
+
+impl Future for Resolving {
+    type Output = io::Result<Vec<Candidate>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock().unwrap();
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Resolving {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Resolving").finish_non_exhaustive()
+    }
+}
+
+fn host_port(connection: &str) -> io::Result<&str> {
+    parse_host_port(connection).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "expected srt://host:port or host:port",
+        )
+    })
+}
+
+/// Extracts `host:port` from an `srt://` URI, dropping any query, or
+/// returns a plain `host:port` as is.
+pub fn parse_host_port(connection: &str) -> Option<&str> {
+    let rest = connection.strip_prefix("srt://").unwrap_or(connection);
+    let host_port = rest.split(['?', '/']).next()?;
+    (!host_port.is_empty()).then_some(host_port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::tests::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn parses_connection_strings() {
+        assert_eq!(
+            parse_host_port("srt://example.com:9000?streamid=live"),
+            Some("example.com:9000")
+        );
+        assert_eq!(parse_host_port("[::1]:9000"), Some("[::1]:9000"));
+        assert_eq!(parse_host_port("srt://"), None);
+    }
+
+    #[test]
+    fn ranks_probed_candidates_and_caches() {
+        let probes = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&probes);
+        let resolver = Resolver::with_probe(Duration::from_secs(60), move |addr| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            addr.is_ipv4().then_some(Duration::from_millis(5))
+        });
+
+        let candidates = block_on(resolver.resolve_async("srt://127.0.0.1:9000")).unwrap();
+        assert_eq!(candidates[0].addr, "127.0.0.1:9000".parse().unwrap());
+        assert_eq!(candidates[0].rtt, Some(Duration::from_millis(5)));
+
+        let mut target = resolver.target("127.0.0.1:9000");
+        target.resolve().unwrap();
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+        assert!(resolver.resolve("no-port").is_err());
+    }
+
+    #[test]
+    fn targets_resolve_on_every_attempt() {
+        let probes = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&probes);
+        let resolver = Resolver::with_probe(Duration::ZERO, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            None
+        });
+        let mut target = resolver.target("srt://127.0.0.1:9000");
+        target.resolve().unwrap();
+        target.resolve().unwrap();
+        assert_eq!(probes.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn panicking_probe_fails_the_lookup() {
+        let resolver = Resolver::with_probe(Duration::ZERO, |_| panic!("probe failed"));
+        let err = block_on(resolver.resolve_async("127.0.0.1:9000")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert!(block_on(resolver.resolve_async("no-port")).is_err());
+    }
+}
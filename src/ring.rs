@@ -0,0 +1,138 @@
+//! An allocation-free receive path over caller-provided storage.
+
+use std::io;
+
+use crate::transport::SrtTransport;
+
+/// Receives messages into fixed-size slots of a caller-provided buffer.
+///
+/// The storage is split into `SLOTS` equal slots, each holding one message.
+/// Slots are filled and released in order, so the ring never allocates after
+/// construction. Messages longer than a slot are truncated, as with any
+/// short receive buffer.
+#[derive(Debug)]
+pub struct RingReceiver<'a, const SLOTS: usize> {
+    storage: &'a mut [u8],
+    slot_size: usize,
+    lens: [usize; SLOTS],
+    head: usize,
+    len: usize,
+}
+
+impl<'a, const SLOTS: usize> RingReceiver<'a, SLOTS> {
+    /// Splits `storage` into `SLOTS` slots. Any remainder is left unused.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `SLOTS` is zero or `storage` is too small for one byte per
+    /// slot.
+    pub fn new(storage: &'a mut [u8]) -> Self {
+        assert!(SLOTS > 0, "ring needs at least one slot");
+        let slot_size = storage.len() / SLOTS;
+        assert!(slot_size > 0, "storage too small for {SLOTS} slots");
+        Self {
+            storage,
+            slot_size,
+            lens: [0; SLOTS],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Receives the next message from `transport` into a free slot,
+    /// returning the slot's index, or `None` once the peer has closed.
+    ///
+    /// Fails with [`io::ErrorKind::OutOfMemory`] if every slot is in use;
+    /// [`release`](Self::release) the oldest before receiving more.
+    pub fn recv<T: SrtTransport + ?Sized>(&mut self, transport: &T) -> io::Result<Option<usize>> {
+        if self.is_full() {
+            return Err(io::Error::new(
+                io::ErrorKind::OutOfMemory,
+                "all ring slots are in use",
+            ));
+        }
+        let index = (self.head + self.len) % SLOTS;
+        let start = index * self.slot_size;
+        let slot = &mut self.storage[start..start + self.slot_size];
+        match transport.recv(slot)? {
+            0 => Ok(None),
+            n => {
+                self.lens[index] = n;
+                self.len += 1;
+                Ok(Some(index))
+            }
+        }
+    }
+
+    /// The message held in slot `index`, if that slot is in use.
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        if index >= SLOTS || (index + SLOTS - self.head) % SLOTS >= self.len {
+            return None;
+        }
+        let start = index * self.slot_size;
+        Some(&self.storage[start..start + self.lens[index]])
+    }
+
+    /// The oldest message still held, with its slot index.
+    pub fn front(&self) -> Option<(usize, &[u8])> {
+        let index = self.head;
+        self.get(index).map(|message| (index, message))
+    }
+
+    /// Frees the slot of the oldest message, returning its index.
+    pub fn release(&mut self) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let index = self.head;
+        self.head = (self.head + 1) % SLOTS;
+        self.len -= 1;
+        Some(index)
+    }
+
+    /// The largest message a slot holds without truncation.
+    pub fn slot_size(&self) -> usize {
+        self.slot_size
+    }
+
+    /// The number of slots holding messages.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == SLOTS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockConfig, MockTransport};
+
+    #[test]
+    fn fills_and_releases_slots_in_order() {
+        let (local, peer) = MockTransport::pair(MockConfig::default());
+        for message in [&b"one"[..], b"two", b"three"] {
+            peer.send(message).unwrap();
+        }
+
+        let mut storage = [0; 32];
+        let mut ring = RingReceiver::<2>::new(&mut storage);
+        assert_eq!(ring.recv(&local).unwrap(), Some(0));
+        assert_eq!(ring.recv(&local).unwrap(), Some(1));
+        let err = ring.recv(&local).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::OutOfMemory);
+
+        assert_eq!(ring.front(), Some((0, &b"one"[..])));
+        assert_eq!(ring.release(), Some(0));
+        assert_eq!(ring.get(0), None);
+        assert_eq!(ring.recv(&local).unwrap(), Some(0));
+        assert_eq!(ring.get(1), Some(&b"two"[..]));
+        assert_eq!(ring.get(0), Some(&b"three"[..]));
+    }
+}
@@ -0,0 +1,101 @@
+//! Helper tasks owned by a socket.
+
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::cancel::CancellationToken;
+
+/// A transport together with the helper threads that work on it, such as a
+/// stats sampler, keepalive or watchdog.
+///
+/// Each task is handed the transport and a [`CancellationToken`] that is
+/// cancelled when the socket is closed or dropped. Closing then waits for
+/// every task to return, so no task outlives the socket. Tasks must watch
+/// their token, or register [`on_cancel`] callbacks, to stop promptly.
+///
+/// [`on_cancel`]: CancellationToken::on_cancel
+#[derive(Debug)]
+pub struct ScopedSocket<T> {
+    transport: Arc<T>,
+    token: CancellationToken,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl<T> ScopedSocket<T>
+where
+    T: Send + Sync + 'static,
+{
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            token: CancellationToken::new(),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Runs `task` on its own thread until the socket is closed.
+    pub fn spawn<F>(&mut self, task: F)
+    where
+        F: FnOnce(Arc<T>, CancellationToken) + Send + 'static,
+    {
+        let transport = Arc::clone(&self.transport);
+        let token = self.token.child();
+        self.tasks
+            .push(thread::spawn(move || task(transport, token)));
+        self.tasks.retain(|task| !task.is_finished());
+    }
+
+    /// The number of tasks still running.
+    pub fn running(&self) -> usize {
+        self.tasks.iter().filter(|task| !task.is_finished()).count()
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.transport
+    }
+
+    /// Shares the transport, for use outside the scope's own tasks.
+    pub fn transport(&self) -> &Arc<T> {
+        &self.transport
+    }
+
+    /// Stops every task and waits for them to return. Dropping the socket
+    /// does the same.
+    pub fn close(self) {
+        drop(self);
+    }
+}
+
+impl<T> Drop for ScopedSocket<T> {
+    fn drop(&mut self) {
+        self.token.cancel();
+        for task in self.tasks.drain(..) {
+            let _ = task.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn drop_stops_tasks() {
+        let stopped = Arc::new(AtomicUsize::new(0));
+        let mut socket = ScopedSocket::new(AtomicUsize::new(0));
+        for _ in 0..3 {
+            let stopped = Arc::clone(&stopped);
+            socket.spawn(move |ticks, token| {
+                while !token.wait_timeout(Duration::from_millis(1)) {
+                    ticks.fetch_add(1, Ordering::SeqCst);
+                }
+                stopped.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        assert_eq!(socket.running(), 3);
+        drop(socket);
+        assert_eq!(stopped.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,121 @@
+//! A blocking accept loop that dispatches connections to a thread pool.
+
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::cancel::CancellationToken;
+use crate::queue::{BoundedQueue, OverflowPolicy};
+
+/// Accepts connections with `accept` and runs `handler` on each one from a
+/// pool of `pool_size` worker threads, until `token` is cancelled.
+///
+/// `accept` is typically a listener's accept method, such as
+/// `|| listener.accept()`. When every worker is busy and `pool_size` more
+/// connections are waiting for one, accepting pauses until a worker is
+/// free, so a larger burst queues in the listener's backlog rather than in
+/// memory. A handler that panics only loses its own connection; the worker
+/// carries on with the next. Errors from `accept` are passed to `on_error`
+/// and skipped after a short pause.
+///
+/// Cancellation is noticed when `accept` next returns, so a blocking
+/// `accept` should be woken on cancel, for instance by connecting to the
+/// listener from [`CancellationToken::on_cancel`]. Returns once the
+/// connections already accepted have been handled.
+///
+/// # Panics
+///
+/// Panics if `pool_size` is zero.
+pub fn serve<T, A, E, H>(
+    mut accept: A,
+    pool_size: usize,
+    token: &CancellationToken,
+    mut on_error: E,
+    handler: H,
+) where
+    T: Send + 'static,
+    A: FnMut() -> io::Result<T>,
+    E: FnMut(io::Error),
+    H: Fn(T) + Send + Sync + 'static,
+{
+    assert!(pool_size > 0, "pool size must be non-zero");
+    let queue: Arc<BoundedQueue<T>> = Arc::new(BoundedQueue::new(pool_size, OverflowPolicy::Block));
+    let handler = Arc::new(handler);
+    let workers: Vec<_> = (0..pool_size)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let handler = Arc::clone(&handler);
+            thread::spawn(move || {
+                while let Some(conn) = queue.pop() {
+                    let _ = panic::catch_unwind(AssertUnwindSafe(|| handler(conn)));
+                }
+            })
+        })
+        .collect();
+
+    loop {
+        let accepted = accept();
+        if token.is_cancelled() {
+            break;
+        }
+        match accepted {
+            Ok(conn) => {
+                if queue.push(conn).is_err() {
+                    break;
+                }
+            }
+            Err(e) => {
+                on_error(e);
+                // Back off rather than spin while out of file descriptors.
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+    }
+    queue.close();
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn handles_connections_despite_panics_and_accept_errors() {
+        let token = CancellationToken::new();
+        let stop = token.clone();
+        let mut pending = (0..10).map(|conn| match conn {
+            5 => Err(io::Error::from(io::ErrorKind::ConnectionAborted)),
+            conn => Ok(conn),
+        });
+        let accept = move || {
+            pending.next().unwrap_or_else(|| {
+                stop.cancel();
+                Err(io::Error::from(io::ErrorKind::Interrupted))
+            })
+        };
+        let handled = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&handled);
+        let mut errors = Vec::new();
+
+        serve(
+            accept,
+            3,
+            &token,
+            |e| errors.push(e.kind()),
+            move |conn: u32| {
+                if conn % 3 == 0 {
+                    panic!("handler failed");
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+
+        assert_eq!(handled.load(Ordering::SeqCst), 5);
+        assert_eq!(errors, [io::ErrorKind::ConnectionAborted]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+
+use super::Stats;
+
+/// Which side of the connection dropped packets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DropDirection {
+    /// Packets the sender discarded as too late to deliver (`pktSndDrop`).
+    Send,
+    /// Packets the receiver dropped as too late to play (`pktRcvDrop`).
+    Receive,
+}
+
+/// Raised by a [`DropWatcher`] when the drop rate exceeds its threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DropAlert {
+    pub direction: DropDirection,
+    /// Packets dropped since the previous sample.
+    pub dropped: u64,
+    /// Time elapsed since the previous sample.
+    pub interval: Duration,
+    /// Drop rate over `interval`, in packets per second.
+    pub rate: f64,
+}
+
+/// Samples the cumulative drop counters of a socket and reports when either
+/// direction drops packets faster than a configured rate.
+pub struct DropWatcher {
+    threshold: f64,
+    on_alert: Box<dyn FnMut(DropAlert) + Send>,
+    last: Option<(Instant, i32, i32)>,
+}
+
+impl DropWatcher {
+    /// Creates a watcher that calls `on_alert` whenever more than `threshold`
+    /// packets per second are dropped in either direction.
+    ///
+    /// To deliver alerts over a channel, pass a closure that sends on it.
+    pub fn new<F>(threshold: f64, on_alert: F) -> Self
+    where
+        F: FnMut(DropAlert) + Send + 'static,
+    {
+        Self {
+            threshold,
+            on_alert: Box::new(on_alert),
+            last: None,
+        }
+    }
+
+    /// Feeds a statistics snapshot taken at the current instant.
+    pub fn sample(&mut self, stats: &Stats) {
+        self.sample_at(Instant::now(), stats);
+    }
+
+    /// Feeds a statistics snapshot taken at `at`.
+    pub fn sample_at(&mut self, at: Instant, stats: &Stats) {
+        let current = (at, stats.pkt_snd_drop_total, stats.pkt_rcv_drop_total);
+        let Some((prev_at, prev_snd, prev_rcv)) = self.last.replace(current) else {
+            return;
+        };
+        let interval = at.saturating_duration_since(prev_at);
+        if interval.is_zero() {
+            return;
+        }
+        let deltas = [
+            (DropDirection::Send, stats.pkt_snd_drop_total - prev_snd),
+            (DropDirection::Receive, stats.pkt_rcv_drop_total - prev_rcv),
+        ];
+        for (direction, delta) in deltas {
+            // Counters restart from zero on reconnect; skip that sample.
+            let Ok(dropped) = u64::try_from(delta) else {
+                continue;
+            };
+            let rate = dropped as f64 / interval.as_secs_f64();
+            if rate > self.threshold {
+                (self.on_alert)(DropAlert {
+                    direction,
+                    dropped,
+                    interval,
+                    rate,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn alerts_when_rate_exceeds_threshold() {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = DropWatcher::new(5.0, move |alert| tx.send(alert).unwrap());
+        let start = Instant::now();
+
+        watcher.sample_at(start, &Stats::default());
+        let stats = Stats {
+            pkt_snd_drop_total: 3,
+            pkt_rcv_drop_total: 20,
+            ..Stats::default()
+        };
+        watcher.sample_at(start + Duration::from_secs(2), &stats);
+
+        let alert = rx.try_recv().unwrap();
+        assert_eq!(alert.direction, DropDirection::Receive);
+        assert_eq!(alert.dropped, 20);
+        assert_eq!(alert.rate, 10.0);
+        assert!(rx.try_recv().is_err());
+    }
+}
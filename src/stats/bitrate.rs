@@ -0,0 +1,142 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::Stats;
+
+/// A bitrate in bits per second.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct BitsPerSecond(pub f64);
+
+impl BitsPerSecond {
+    /// The rate in megabits per second, the unit libsrt reports.
+    pub fn as_mbps(self) -> f64 {
+        self.0 / 1_000_000.0
+    }
+}
+
+impl fmt::Display for BitsPerSecond {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3} Mbps", self.as_mbps())
+    }
+}
+
+/// An exponentially weighted moving average of a byte counter's rate.
+///
+/// Each sample's instantaneous rate is blended in with weight
+/// `1 - exp(-dt / window)`, so irregular sampling intervals are handled
+/// correctly and `window` acts as the averaging time constant.
+#[derive(Debug, Clone)]
+pub struct BitrateMeter {
+    window: Duration,
+    last: Option<(Instant, u64)>,
+    rate: Option<f64>,
+}
+
+impl BitrateMeter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last: None,
+            rate: None,
+        }
+    }
+
+    /// A meter averaging over roughly one second.
+    pub fn one_second() -> Self {
+        Self::new(Duration::from_secs(1))
+    }
+
+    /// A meter averaging over roughly ten seconds.
+    pub fn ten_seconds() -> Self {
+        Self::new(Duration::from_secs(10))
+    }
+
+    /// Records the cumulative byte count `total_bytes` observed at `at`,
+    /// returning the updated average.
+    pub fn update_at(&mut self, at: Instant, total_bytes: u64) -> BitsPerSecond {
+        if let Some((last_at, last_bytes)) = self.last {
+            let dt = at.saturating_duration_since(last_at);
+            if dt.is_zero() {
+                return self.rate();
+            }
+            let bits = total_bytes.saturating_sub(last_bytes) as f64 * 8.0;
+            let instant = bits / dt.as_secs_f64();
+            let alpha = 1.0 - (-dt.as_secs_f64() / self.window.as_secs_f64()).exp();
+            self.rate = Some(match self.rate {
+                Some(rate) => rate + alpha * (instant - rate),
+                None => instant,
+            });
+        }
+        self.last = Some((at, total_bytes));
+        self.rate()
+    }
+
+    /// The current average, zero until two samples have been recorded.
+    pub fn rate(&self) -> BitsPerSecond {
+        BitsPerSecond(self.rate.unwrap_or(0.0))
+    }
+}
+
+/// Tracks smoothed send and receive bitrates from a stream of [`Stats`].
+#[derive(Debug, Clone)]
+pub struct BitrateTracker {
+    send: BitrateMeter,
+    recv: BitrateMeter,
+}
+
+impl BitrateTracker {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            send: BitrateMeter::new(window),
+            recv: BitrateMeter::new(window),
+        }
+    }
+
+    /// Feeds a statistics snapshot taken at the current instant.
+    pub fn sample(&mut self, stats: &Stats) {
+        self.sample_at(Instant::now(), stats);
+    }
+
+    /// Feeds a statistics snapshot taken at `at`.
+    pub fn sample_at(&mut self, at: Instant, stats: &Stats) {
+        self.send.update_at(at, stats.byte_sent_total);
+        self.recv.update_at(at, stats.byte_recv_total);
+    }
+
+    pub fn send_rate(&self) -> BitsPerSecond {
+        self.send.rate()
+    }
+
+    pub fn recv_rate(&self) -> BitsPerSecond {
+        self.recv.rate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steady_rate_is_reported_exactly() {
+        let mut meter = BitrateMeter::one_second();
+        let start = Instant::now();
+        for i in 0..20u64 {
+            meter.update_at(start + Duration::from_millis(i * 100), i * 12_500);
+        }
+        assert!((meter.rate().0 - 1_000_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn longer_window_reacts_more_slowly() {
+        let start = Instant::now();
+        let mut fast = BitrateMeter::one_second();
+        let mut slow = BitrateMeter::ten_seconds();
+        for meter in [&mut fast, &mut slow] {
+            meter.update_at(start, 0);
+            meter.update_at(start + Duration::from_secs(1), 125_000);
+            meter.update_at(start + Duration::from_secs(2), 375_000);
+        }
+        assert!(fast.rate() > slow.rate());
+        assert!(slow.rate() > BitsPerSecond(1_000_000.0));
+    }
+}
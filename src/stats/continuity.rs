@@ -0,0 +1,132 @@
+use super::Stats;
+
+/// How [`ContinuousStats`] treats the counters of a replaced connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatsContinuity {
+    /// Report each connection's counters as they are, starting from zero
+    /// after every reconnect.
+    #[default]
+    Reset,
+    /// Add the final counters of earlier connections to those of the
+    /// current one, so cumulative figures never go backwards.
+    Accumulate,
+}
+
+/// Smooths over the counter resets caused by reconnecting.
+///
+/// Feed in each snapshot along with the connection generation, such as
+/// [`Reconnecting::generation`](crate::reconnect::Reconnecting::generation);
+/// a change of generation marks a new underlying socket.
+#[derive(Debug, Clone)]
+pub struct ContinuousStats {
+    mode: StatsContinuity,
+    generation: Option<u64>,
+    base: Stats,
+    last: Stats,
+}
+
+impl ContinuousStats {
+    pub fn new(mode: StatsContinuity) -> Self {
+        Self {
+            mode,
+            generation: None,
+            base: Stats::default(),
+            last: Stats::default(),
+        }
+    }
+
+    /// Records `stats` from connection `generation`, returning the figures
+    /// to expose.
+    ///
+    /// In [`StatsContinuity::Accumulate`] mode the `*_total` counters and
+    /// `ms_timestamp` carry on from earlier connections; interval and
+    /// instantaneous fields always describe the current one.
+    pub fn update(&mut self, generation: u64, stats: &Stats) -> Stats {
+        if self.generation.is_some_and(|current| current != generation) {
+            self.base = accumulate(&self.base, &self.last);
+        }
+        self.generation = Some(generation);
+        self.last = *stats;
+        match self.mode {
+            StatsContinuity::Reset => *stats,
+            StatsContinuity::Accumulate => accumulate(&self.base, stats),
+        }
+    }
+
+    /// The generation of the most recent snapshot.
+    pub fn generation(&self) -> Option<u64> {
+        self.generation
+    }
+}
+
+/// `stats` with the cumulative fields of `base` added, saturating rather
+/// than wrapping on overflow.
+fn accumulate(base: &Stats, stats: &Stats) -> Stats {
+    Stats {
+        ms_timestamp: base.ms_timestamp.saturating_add(stats.ms_timestamp),
+        pkt_sent_total: base.pkt_sent_total.saturating_add(stats.pkt_sent_total),
+        pkt_recv_total: base.pkt_recv_total.saturating_add(stats.pkt_recv_total),
+        pkt_snd_loss_total: base
+            .pkt_snd_loss_total
+            .saturating_add(stats.pkt_snd_loss_total),
+        pkt_rcv_loss_total: base
+            .pkt_rcv_loss_total
+            .saturating_add(stats.pkt_rcv_loss_total),
+        pkt_retrans_total: base
+            .pkt_retrans_total
+            .saturating_add(stats.pkt_retrans_total),
+        pkt_rcv_retrans_total: base
+            .pkt_rcv_retrans_total
+            .saturating_add(stats.pkt_rcv_retrans_total),
+        pkt_snd_drop_total: base
+            .pkt_snd_drop_total
+            .saturating_add(stats.pkt_snd_drop_total),
+        pkt_rcv_drop_total: base
+            .pkt_rcv_drop_total
+            .saturating_add(stats.pkt_rcv_drop_total),
+        byte_sent_total: base.byte_sent_total.saturating_add(stats.byte_sent_total),
+        byte_recv_total: base.byte_recv_total.saturating_add(stats.byte_recv_total),
+        ..*stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent(pkt_sent_total: i64, ms_rtt: f64) -> Stats {
+        Stats {
+            pkt_sent_total,
+            ms_rtt,
+            ..Stats::default()
+        }
+    }
+
+    #[test]
+    fn accumulates_across_generations() {
+        let mut stats = ContinuousStats::new(StatsContinuity::Accumulate);
+        assert_eq!(stats.update(1, &sent(100, 10.0)).pkt_sent_total, 100);
+        assert_eq!(stats.update(1, &sent(150, 10.0)).pkt_sent_total, 150);
+        let after = stats.update(2, &sent(20, 30.0));
+        assert_eq!(after.pkt_sent_total, 170);
+        assert_eq!(after.ms_rtt, 30.0);
+        assert_eq!(stats.update(3, &sent(5, 30.0)).pkt_sent_total, 175);
+
+        let mut stats = ContinuousStats::new(StatsContinuity::Reset);
+        stats.update(1, &sent(150, 10.0));
+        assert_eq!(stats.update(2, &sent(20, 10.0)).pkt_sent_total, 20);
+        assert_eq!(stats.generation(), Some(2));
+    }
+
+    #[test]
+    fn saturates_instead_of_overflowing() {
+        let drops = |pkt_snd_drop_total| Stats {
+            pkt_snd_drop_total,
+            ..Stats::default()
+        };
+        let mut stats = ContinuousStats::new(StatsContinuity::Accumulate);
+        stats.update(1, &drops(i32::MAX));
+        let after = stats.update(2, &drops(1));
+        assert_eq!(after.pkt_snd_drop_total, i32::MAX);
+    }
+}
@@ -0,0 +1,105 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use super::fields::{fields, rfc3339};
+use super::Stats;
+
+/// Appends statistics snapshots to CSV, one row per socket per sample.
+///
+/// Columns follow srt-live-transmit's `-statspf:csv` output, restricted to
+/// the fields [`Stats`] carries, so existing tooling can read both. Each row
+/// begins with a wall-clock `Timepoint` and a `SocketID` label, which lets
+/// one file collect samples from several sockets.
+pub struct StatsCsvWriter<W: Write> {
+    writer: W,
+    header_written: bool,
+}
+
+impl StatsCsvWriter<BufWriter<File>> {
+    /// Opens `path` for appending, writing the header only if the file is
+    /// new or empty.
+    pub fn append(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let header_written = file.metadata()?.len() > 0;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            header_written,
+        })
+    }
+}
+
+impl<W: Write> StatsCsvWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header_written: false,
+        }
+    }
+
+    /// Writes a row for `socket` sampled now.
+    pub fn write(&mut self, socket: &str, stats: &Stats) -> io::Result<()> {
+        self.write_at(SystemTime::now(), socket, stats)
+    }
+
+    /// Writes a row for `socket` sampled at `time`.
+    pub fn write_at(&mut self, time: SystemTime, socket: &str, stats: &Stats) -> io::Result<()> {
+        let fields = fields(stats);
+        if !self.header_written {
+            write!(self.writer, "Timepoint,SocketID")?;
+            for (name, _) in &fields {
+                write!(self.writer, ",{name}")?;
+            }
+            writeln!(self.writer)?;
+            self.header_written = true;
+        }
+        write!(self.writer, "{},{}", rfc3339(time), escape(socket))?;
+        for (_, value) in &fields {
+            write!(self.writer, ",{value}")?;
+        }
+        writeln!(self.writer)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn writes_header_once() {
+        let mut writer = StatsCsvWriter::new(Vec::new());
+        let time = UNIX_EPOCH + Duration::from_secs(60);
+        let stats = Stats {
+            ms_rtt: 12.5,
+            pkt_sent: 100,
+            ..Stats::default()
+        };
+        writer.write_at(time, "cam,1", &stats).unwrap();
+        writer.write_at(time, "cam2", &stats).unwrap();
+
+        let csv = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Timepoint,SocketID,Time,pktFlowWindow,"));
+        assert!(lines[1].starts_with("1970-01-01T00:01:00.000Z,\"cam,1\",0,0,0,0,12.5,"));
+        assert_eq!(lines[0].split(',').count(), lines[2].split(',').count());
+    }
+}
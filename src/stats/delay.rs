@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Tracks per-message one-way delay over a sliding window.
+///
+/// Feed each received message's `srctime`, taken relative to the socket's
+/// connection time (`srt_connection_time`), together with its local arrival
+/// time. libsrt expresses `srctime` in the receiver's clock, so no external
+/// clock synchronisation is needed, though the figures include any drift
+/// the drift tracer has not yet corrected.
+#[derive(Debug, Clone)]
+pub struct OneWayDelay {
+    connected_at: Instant,
+    window: Duration,
+    samples: VecDeque<(Instant, Duration)>,
+}
+
+impl OneWayDelay {
+    /// Creates a tracker for a connection established at `connected_at`,
+    /// keeping samples that arrived within the last `window`.
+    pub fn new(connected_at: Instant, window: Duration) -> Self {
+        Self {
+            connected_at,
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Records a message stamped `sent` after the connection was
+    /// established that arrived at `arrival`, returning its delay.
+    ///
+    /// A stamp too large to be added to the connection time comes from a
+    /// misbehaving peer; it is skipped and `None` is returned.
+    pub fn update(&mut self, sent: Duration, arrival: Instant) -> Option<Duration> {
+        let sent_at = self.connected_at.checked_add(sent)?;
+        let delay = arrival.saturating_duration_since(sent_at);
+        self.samples.push_back((arrival, delay));
+        self.expire(arrival);
+        Some(delay)
+    }
+
+    /// Drops samples that arrived more than the window before `now`.
+    pub fn expire(&mut self, now: Instant) {
+        while self
+            .samples
+            .front()
+            .is_some_and(|&(arrival, _)| now.saturating_duration_since(arrival) > self.window)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// The number of samples in the window.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn min(&self) -> Option<Duration> {
+        self.samples.iter().map(|&(_, delay)| delay).min()
+    }
+
+    pub fn avg(&self) -> Option<Duration> {
+        let count = u32::try_from(self.samples.len()).ok().filter(|&n| n > 0)?;
+        Some(
+            self.samples
+                .iter()
+                .map(|&(_, delay)| delay)
+                .sum::<Duration>()
+                / count,
+        )
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.samples.iter().map(|&(_, delay)| delay).max()
+    }
+
+    /// The 99th percentile delay, by the nearest-rank method.
+    pub fn p99(&self) -> Option<Duration> {
+        self.percentile(99.0)
+    }
+
+    /// The `p`th percentile delay, for `p` between 0 and 100, by the
+    /// nearest-rank method.
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut delays: Vec<Duration> = self.samples.iter().map(|&(_, delay)| delay).collect();
+        delays.sort_unstable();
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * delays.len() as f64).ceil() as usize;
+        Some(delays[rank.saturating_sub(1)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarises_window() {
+        let start = Instant::now();
+        let mut delays = OneWayDelay::new(start, Duration::from_secs(1));
+        for i in 0..100u64 {
+            let sent = Duration::from_millis(i * 5);
+            let delay = Duration::from_millis(if i == 50 { 200 } else { 20 + i % 3 });
+            assert_eq!(delays.update(sent, start + sent + delay), Some(delay));
+        }
+        assert_eq!(delays.update(Duration::MAX, start), None);
+        assert_eq!(delays.len(), 100);
+        assert_eq!(delays.min(), Some(Duration::from_millis(20)));
+        assert_eq!(delays.max(), Some(Duration::from_millis(200)));
+        assert_eq!(delays.p99(), Some(Duration::from_millis(22)));
+        assert_eq!(delays.percentile(100.0), Some(Duration::from_millis(200)));
+
+        delays.expire(start + Duration::from_secs(3));
+        assert!(delays.is_empty());
+        assert_eq!(delays.avg(), None);
+    }
+}
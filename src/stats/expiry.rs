@@ -0,0 +1,184 @@
+use std::time::{Duration, Instant};
+
+use super::Stats;
+
+/// Raised by a [`TtlWatcher`] when the sender dropped messages as too late.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtlExpiry {
+    /// Packets dropped (`pktSndDrop`) since the last sample taken before
+    /// the earliest of the expired messages ran out.
+    pub dropped: u64,
+    /// Message numbers of the tracked messages whose TTL ran out, any of
+    /// which may have been dropped.
+    pub expired: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Tracked {
+    deadline: Instant,
+    msgno: u32,
+    /// `pkt_snd_drop_total` at the last sample before the deadline, or
+    /// `None` until the first sample after tracking began.
+    baseline: Option<i32>,
+    /// Whether the message has already been given one sample past its
+    /// deadline for the drop to be counted.
+    overdue: bool,
+}
+
+/// Reports messages sent with a TTL that the sender may have dropped.
+///
+/// libsrt only counts sender drops, so the watcher pairs an increase in
+/// `pkt_snd_drop_total`, measured from the last sample before a message's
+/// TTL ran out, with the tracked messages that have expired. A drop counted
+/// in the sample after the expiry is still matched. Track only the messages
+/// worth retransmitting, such as keyed control messages; when no drops are
+/// counted their expiry is forgotten.
+pub struct TtlWatcher {
+    pending: Vec<Tracked>,
+    last_drops: Option<i32>,
+    on_expiry: Box<dyn FnMut(TtlExpiry) + Send>,
+}
+
+impl TtlWatcher {
+    pub fn new<F>(on_expiry: F) -> Self
+    where
+        F: FnMut(TtlExpiry) + Send + 'static,
+    {
+        Self {
+            pending: Vec::new(),
+            last_drops: None,
+            on_expiry: Box::new(on_expiry),
+        }
+    }
+
+    /// Tracks message `msgno`, sent just now with `ttl`.
+    pub fn track(&mut self, msgno: u32, ttl: Duration) {
+        self.track_at(Instant::now(), msgno, ttl);
+    }
+
+    /// Tracks message `msgno`, sent at `sent` with `ttl`.
+    pub fn track_at(&mut self, sent: Instant, msgno: u32, ttl: Duration) {
+        self.pending.push(Tracked {
+            deadline: sent + ttl,
+            msgno,
+            baseline: self.last_drops,
+            overdue: false,
+        });
+    }
+
+    /// The number of tracked messages not yet reported or forgotten.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Feeds a statistics snapshot taken at the current instant.
+    pub fn sample(&mut self, stats: &Stats) {
+        self.sample_at(Instant::now(), stats);
+    }
+
+    /// Feeds a statistics snapshot taken at `at`.
+    pub fn sample_at(&mut self, at: Instant, stats: &Stats) {
+        let drops = stats.pkt_snd_drop_total;
+        self.last_drops = Some(drops);
+        let mut dropped = 0;
+        let mut expired = Vec::new();
+        self.pending.retain_mut(|tracked| {
+            // Drops counted before the first sample predate the message.
+            let baseline = *tracked.baseline.get_or_insert(drops);
+            if at < tracked.deadline {
+                tracked.baseline = Some(drops);
+                return true;
+            }
+            // Counters restart from zero on reconnect; count no drops then.
+            let delta = u64::try_from(drops - baseline).unwrap_or(0);
+            if delta > 0 {
+                dropped = dropped.max(delta);
+                expired.push(tracked.msgno);
+                return false;
+            }
+            let keep = !tracked.overdue;
+            tracked.overdue = true;
+            keep
+        });
+        if !expired.is_empty() {
+            (self.on_expiry)(TtlExpiry { dropped, expired });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn reports_expired_messages_when_drops_are_counted() {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = TtlWatcher::new(move |expiry| tx.send(expiry).unwrap());
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        watcher.track_at(start, 1, ms(50));
+        watcher.track_at(start, 2, ms(500));
+
+        let drops = |pkt_snd_drop_total| Stats {
+            pkt_snd_drop_total,
+            ..Stats::default()
+        };
+        watcher.sample_at(start, &drops(0));
+        watcher.sample_at(start + ms(100), &drops(0));
+        watcher.sample_at(start + ms(200), &drops(1));
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            TtlExpiry {
+                dropped: 1,
+                expired: vec![1],
+            }
+        );
+
+        watcher.sample_at(start + ms(600), &drops(1));
+        watcher.sample_at(start + ms(700), &drops(1));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(watcher.pending(), 0);
+    }
+
+    #[test]
+    fn matches_drops_counted_with_the_expiry() {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = TtlWatcher::new(move |expiry| tx.send(expiry).unwrap());
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let drops = |pkt_snd_drop_total| Stats {
+            pkt_snd_drop_total,
+            ..Stats::default()
+        };
+        watcher.sample_at(start, &drops(4));
+        watcher.track_at(start, 7, ms(50));
+        watcher.sample_at(start + ms(40), &drops(4));
+        watcher.sample_at(start + ms(100), &drops(6));
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            TtlExpiry {
+                dropped: 2,
+                expired: vec![7],
+            }
+        );
+        assert_eq!(watcher.pending(), 0);
+    }
+
+    #[test]
+    fn ignores_drops_counted_before_the_first_sample() {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = TtlWatcher::new(move |expiry| tx.send(expiry).unwrap());
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let drops = |pkt_snd_drop_total| Stats {
+            pkt_snd_drop_total,
+            ..Stats::default()
+        };
+        watcher.track_at(start, 3, ms(50));
+        watcher.sample_at(start + ms(100), &drops(5));
+        watcher.sample_at(start + ms(200), &drops(5));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(watcher.pending(), 0);
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::Stats;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Field {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Int(value) => value.fmt(f),
+            Field::UInt(value) => value.fmt(f),
+            Field::Float(value) => value.fmt(f),
+        }
+    }
+}
+
+/// The per-socket fields of srt-live-transmit's CSV stats output that
+/// [`Stats`] carries, in the same order and under the same names.
+pub(crate) fn fields(stats: &Stats) -> [(&'static str, Field); 25] {
+    use Field::{Float, Int, UInt};
+    [
+        ("Time", Int(stats.ms_timestamp)),
+        ("pktFlowWindow", Int(stats.pkt_flow_window.into())),
+        (
+            "pktCongestionWindow",
+            Int(stats.pkt_congestion_window.into()),
+        ),
+        ("pktFlightSize", Int(stats.pkt_flight_size.into())),
+        ("msRTT", Float(stats.ms_rtt)),
+        ("mbpsBandwidth", Float(stats.mbps_bandwidth)),
+        ("mbpsMaxBW", Float(stats.mbps_max_bw)),
+        ("pktSent", Int(stats.pkt_sent)),
+        ("pktSndLoss", Int(stats.pkt_snd_loss.into())),
+        ("pktSndDrop", Int(stats.pkt_snd_drop.into())),
+        ("pktRetrans", Int(stats.pkt_retrans.into())),
+        ("byteSent", UInt(stats.byte_sent)),
+        ("byteAvailSndBuf", Int(stats.byte_avail_snd_buf.into())),
+        ("mbpsSendRate", Float(stats.mbps_send_rate)),
+        ("usPktSndPeriod", Float(stats.us_pkt_snd_period)),
+        ("msSndBuf", Int(stats.ms_snd_buf.into())),
+        ("pktRecv", Int(stats.pkt_recv)),
+        ("pktRcvLoss", Int(stats.pkt_rcv_loss.into())),
+        ("pktRcvDrop", Int(stats.pkt_rcv_drop.into())),
+        ("pktRcvRetrans", Int(stats.pkt_rcv_retrans.into())),
+        ("byteRecv", UInt(stats.byte_recv)),
+        ("byteAvailRcvBuf", Int(stats.byte_avail_rcv_buf.into())),
+        ("mbpsRecvRate", Float(stats.mbps_recv_rate)),
+        ("msRcvBuf", Int(stats.ms_rcv_buf.into())),
+        ("msRcvTsbPdDelay", Int(stats.ms_rcv_tsbpd_delay.into())),
+    ]
+}
+
+/// Formats `time` as an RFC 3339 UTC timestamp with millisecond precision.
+pub(crate) fn rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Civil-from-days, from Howard Hinnant's date algorithms.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        rem / 3_600,
+        rem / 60 % 60,
+        rem % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn formats_rfc3339() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+        assert_eq!(rfc3339(time), "2024-02-29T12:34:56.789Z");
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+    }
+}
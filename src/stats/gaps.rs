@@ -0,0 +1,170 @@
+use std::time::{Duration, Instant};
+
+/// The modulus of SRT packet sequence numbers, which are 31 bits wide.
+pub(crate) const SEQUENCE_SPACE: u32 = 1 << 31;
+/// The last SRT message number. The field is 26 bits wide, but libsrt
+/// never sends 0 or the all-ones value, wrapping from this back to 1.
+pub(crate) const MESSAGE_LAST: u32 = (1 << 26) - 2;
+
+/// A run of numbers missing from a received stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GapEvent {
+    /// The first missing number.
+    pub first_missing: u32,
+    /// How many consecutive numbers are missing.
+    pub count: u32,
+    /// When the number preceding the gap arrived.
+    pub before: Instant,
+    /// When the number following the gap arrived.
+    pub after: Instant,
+}
+
+impl GapEvent {
+    /// The time between the arrivals on either side of the gap.
+    pub fn span(&self) -> Duration {
+        self.after.saturating_duration_since(self.before)
+    }
+}
+
+/// Reports gaps in the packet sequence or message numbers of received
+/// messages, as taken from `SRT_MSGCTRL`.
+///
+/// A number behind the highest seen so far, such as a late retransmission,
+/// is counted as reordered rather than reported.
+pub struct GapDetector {
+    /// The lowest number in use.
+    first: u32,
+    /// How many numbers are in use before they wrap back to `first`.
+    space: u32,
+    last: Option<(u32, Instant)>,
+    gaps: u64,
+    missing: u64,
+    reordered: u64,
+    on_gap: Box<dyn FnMut(GapEvent) + Send>,
+}
+
+impl GapDetector {
+    /// Tracks packet sequence numbers (`pktseq`).
+    pub fn sequence<F>(on_gap: F) -> Self
+    where
+        F: FnMut(GapEvent) + Send + 'static,
+    {
+        Self::with_range(0, SEQUENCE_SPACE, on_gap)
+    }
+
+    /// Tracks message numbers (`msgno`).
+    pub fn message_number<F>(on_gap: F) -> Self
+    where
+        F: FnMut(GapEvent) + Send + 'static,
+    {
+        Self::with_range(1, MESSAGE_LAST, on_gap)
+    }
+
+    fn with_range<F>(first: u32, space: u32, on_gap: F) -> Self
+    where
+        F: FnMut(GapEvent) + Send + 'static,
+    {
+        Self {
+            first,
+            space,
+            last: None,
+            gaps: 0,
+            missing: 0,
+            reordered: 0,
+            on_gap: Box::new(on_gap),
+        }
+    }
+
+    /// Records the number of a message received at the current instant.
+    pub fn observe(&mut self, number: u32) {
+        self.observe_at(Instant::now(), number);
+    }
+
+    /// Records the number of a message received at `at`. Numbers never
+    /// sent, such as a message number of 0, are ignored.
+    pub fn observe_at(&mut self, at: Instant, number: u32) {
+        let Some(number) = number
+            .checked_sub(self.first)
+            .filter(|&number| number < self.space)
+        else {
+            return;
+        };
+        let Some((last, before)) = self.last else {
+            self.last = Some((number, at));
+            return;
+        };
+        let ahead = (number + self.space - last) % self.space;
+        if ahead == 0 || ahead >= self.space / 2 {
+            self.reordered += 1;
+            return;
+        }
+        if ahead > 1 {
+            let event = GapEvent {
+                first_missing: (last + 1) % self.space + self.first,
+                count: ahead - 1,
+                before,
+                after: at,
+            };
+            self.gaps += 1;
+            self.missing += u64::from(event.count);
+            (self.on_gap)(event);
+        }
+        self.last = Some((number, at));
+    }
+
+    /// Gaps reported so far.
+    pub fn gaps(&self) -> u64 {
+        self.gaps
+    }
+
+    /// Numbers reported missing so far, across all gaps.
+    pub fn missing(&self) -> u64 {
+        self.missing
+    }
+
+    /// Numbers received behind, or equal to, the highest seen.
+    pub fn reordered(&self) -> u64 {
+        self.reordered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn reports_gaps_across_wraparound() {
+        let (tx, rx) = mpsc::channel();
+        let mut detector = GapDetector::sequence(move |event| tx.send(event).unwrap());
+        let start = Instant::now();
+        let max = SEQUENCE_SPACE - 1;
+        for (i, seq) in [max - 2, max - 1, 1, 2, 0, 5].into_iter().enumerate() {
+            detector.observe_at(start + Duration::from_millis(i as u64 * 10), seq);
+        }
+
+        let events: Vec<_> = rx.try_iter().collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].first_missing, events[0].count), (max, 2));
+        assert_eq!(events[0].span(), Duration::from_millis(10));
+        assert_eq!((events[1].first_missing, events[1].count), (3, 2));
+        assert_eq!(events[1].span(), Duration::from_millis(20));
+        assert_eq!(detector.missing(), 4);
+        assert_eq!(detector.reordered(), 1);
+    }
+
+    #[test]
+    fn message_numbers_wrap_to_one() {
+        let (tx, rx) = mpsc::channel();
+        let mut detector = GapDetector::message_number(move |event| tx.send(event).unwrap());
+        let start = Instant::now();
+        for msgno in [MESSAGE_LAST - 1, MESSAGE_LAST, 1, 0, 3] {
+            detector.observe_at(start, msgno);
+        }
+
+        let events: Vec<_> = rx.try_iter().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].first_missing, events[0].count), (2, 1));
+        assert_eq!(detector.reordered(), 0);
+    }
+}
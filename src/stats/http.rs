@@ -0,0 +1,314 @@
+use std::fmt::Write as _;
+use std::time::SystemTime;
+
+use super::fields::{fields, Field};
+use super::ndjson::to_json;
+use super::Stats;
+
+/// `Content-Type` of [`render_prometheus`] output.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+/// `Content-Type` of [`render_json`] output.
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Renders per-socket snapshots, labelled by socket, as a JSON document.
+///
+/// The document has a `sockets` array holding one object per socket, in the
+/// format of [`StatsNdjsonWriter`](super::StatsNdjsonWriter), and an
+/// `aggregate` object summing rates and lifetime counters across them.
+pub fn render_json(sockets: &[(&str, Stats)]) -> String {
+    let now = SystemTime::now();
+    let mut json = String::from("{\"sockets\":[");
+    for (i, (socket, stats)) in sockets.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str(&to_json(now, socket, stats));
+    }
+    write!(json, "],\"aggregate\":{{\"sockets\":{}", sockets.len()).unwrap();
+    for (name, value) in aggregate(sockets) {
+        match value {
+            Field::Float(value) if !value.is_finite() => write!(json, ",\"{name}\":null"),
+            value => write!(json, ",\"{name}\":{value}"),
+        }
+        .unwrap();
+    }
+    json.push_str("}}");
+    json
+}
+
+fn aggregate(sockets: &[(&str, Stats)]) -> [(&'static str, Field); 10] {
+    let sum_int = |f: fn(&Stats) -> i64| Field::Int(sockets.iter().map(|(_, s)| f(s)).sum());
+    let sum_uint = |f: fn(&Stats) -> u64| Field::UInt(sockets.iter().map(|(_, s)| f(s)).sum());
+    let sum_float = |f: fn(&Stats) -> f64| Field::Float(sockets.iter().map(|(_, s)| f(s)).sum());
+    [
+        ("mbpsSendRate", sum_float(|s| s.mbps_send_rate)),
+        ("mbpsRecvRate", sum_float(|s| s.mbps_recv_rate)),
+        ("pktSentTotal", sum_int(|s| s.pkt_sent_total)),
+        ("pktRecvTotal", sum_int(|s| s.pkt_recv_total)),
+        ("pktSndLossTotal", sum_int(|s| s.pkt_snd_loss_total.into())),
+        ("pktRcvLossTotal", sum_int(|s| s.pkt_rcv_loss_total.into())),
+        ("pktSndDropTotal", sum_int(|s| s.pkt_snd_drop_total.into())),
+        ("pktRcvDropTotal", sum_int(|s| s.pkt_rcv_drop_total.into())),
+        ("byteSentTotal", sum_uint(|s| s.byte_sent_total)),
+        ("byteRecvTotal", sum_uint(|s| s.byte_recv_total)),
+    ]
+}
+
+/// Renders per-socket snapshots in the Prometheus text exposition format.
+///
+/// Each field becomes an `srt_`-prefixed snake case gauge, such as
+/// `srt_ms_rtt`, labelled with `socket`. Lifetime counters are exported as
+/// `srt_*_total` counters. Aggregate figures are left to PromQL's `sum`.
+pub fn render_prometheus(sockets: &[(&str, Stats)]) -> String {
+    let mut text = String::new();
+    writeln!(
+        text,
+        "# TYPE srt_sockets gauge\nsrt_sockets {}",
+        sockets.len()
+    )
+    .unwrap();
+    let rows: Vec<_> = sockets
+        .iter()
+        .map(|(socket, stats)| (label(socket), fields(stats), totals(stats)))
+        .collect();
+    let Some((_, first_fields, first_totals)) = rows.first() else {
+        return text;
+    };
+
+    // "Time" is the connection age, not a metric worth exporting.
+    for column in 1..first_fields.len() {
+        let name = snake_case(first_fields[column].0);
+        writeln!(text, "# TYPE srt_{name} gauge").unwrap();
+        for (socket, fields, _) in &rows {
+            writeln!(text, "srt_{name}{{socket={socket}}} {}", fields[column].1).unwrap();
+        }
+    }
+    for column in 0..first_totals.len() {
+        let name = first_totals[column].0;
+        writeln!(text, "# TYPE srt_{name} counter").unwrap();
+        for (socket, _, totals) in &rows {
+            writeln!(text, "srt_{name}{{socket={socket}}} {}", totals[column].1).unwrap();
+        }
+    }
+    text
+}
+
+fn totals(stats: &Stats) -> [(&'static str, Field); 10] {
+    use Field::{Int, UInt};
+    [
+        ("pkt_sent_total", Int(stats.pkt_sent_total)),
+        ("pkt_recv_total", Int(stats.pkt_recv_total)),
+        ("pkt_snd_loss_total", Int(stats.pkt_snd_loss_total.into())),
+        ("pkt_rcv_loss_total", Int(stats.pkt_rcv_loss_total.into())),
+        ("pkt_retrans_total", Int(stats.pkt_retrans_total.into())),
+        (
+            "pkt_rcv_retrans_total",
+            Int(stats.pkt_rcv_retrans_total.into()),
+        ),
+        ("pkt_snd_drop_total", Int(stats.pkt_snd_drop_total.into())),
+        ("pkt_rcv_drop_total", Int(stats.pkt_rcv_drop_total.into())),
+        ("byte_sent_total", UInt(stats.byte_sent_total)),
+        ("byte_recv_total", UInt(stats.byte_recv_total)),
+    ]
+}
+
+/// Quotes a label value, escaping as the exposition format requires.
+fn label(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+/// Converts srt-live-transmit's field names, like `msRTT` or
+/// `mbpsMaxBW`, to snake case: `ms_rtt`, `mbps_max_bw`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let after_lower = i > 0 && chars[i - 1].is_ascii_lowercase();
+            let before_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            let after_upper = i > 0 && chars[i - 1].is_ascii_uppercase();
+            if after_lower || (after_upper && before_lower) {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Answers a request for `path` from the snapshots in `sockets`, returning
+/// the content type and body, or `None` for an unknown path.
+///
+/// `/metrics` serves [`render_prometheus`] and `/stats` serves
+/// [`render_json`]; mount this in an existing HTTP server to expose them.
+pub fn respond(path: &str, sockets: &[(&str, Stats)]) -> Option<(&'static str, String)> {
+    match path.split('?').next()? {
+        "/metrics" => Some((PROMETHEUS_CONTENT_TYPE, render_prometheus(sockets))),
+        "/stats" => Some((JSON_CONTENT_TYPE, render_json(sockets))),
+        _ => None,
+    }
+}
+
+/// Worker threads [`serve_http`] handles requests on.
+#[cfg(feature = "http-stats")]
+const HTTP_WORKERS: usize = 4;
+
+/// Serves [`respond`] over HTTP/1.0 on `listener` until `token` is
+/// cancelled, calling `source` for fresh snapshots on each request.
+///
+/// Requests are handled by a small pool of worker threads through
+/// [`serve`](crate::server::serve), so a slow client does not hold up other
+/// scrapes and a burst of connections waits in the listener's backlog.
+/// Failed connections are dropped. Blocks the calling thread; run it on one
+/// of its own.
+#[cfg(feature = "http-stats")]
+pub fn serve_http<F>(
+    listener: std::net::TcpListener,
+    token: &crate::cancel::CancellationToken,
+    source: F,
+) -> std::io::Result<()>
+where
+    F: Fn() -> Vec<(String, Stats)> + Send + Sync + 'static,
+{
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+    use std::time::Duration;
+
+    // Wake the blocked accept with a connection of our own on cancel.
+    let mut wake = listener.local_addr()?;
+    if wake.ip().is_unspecified() {
+        wake.set_ip(match wake {
+            SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+            SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+        });
+    }
+    let _registration = token.on_cancel(move || {
+        let _ = TcpStream::connect_timeout(&wake, Duration::from_secs(1));
+    });
+
+    crate::server::serve(
+        || listener.accept().map(|(stream, _)| stream),
+        HTTP_WORKERS,
+        token,
+        |_| {},
+        move |stream| {
+            let _ = handle_request(stream, &source);
+        },
+    );
+    Ok(())
+}
+
+#[cfg(feature = "http-stats")]
+fn handle_request(
+    mut stream: std::net::TcpStream,
+    source: &dyn Fn() -> Vec<(String, Stats)>,
+) -> std::io::Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::time::Duration;
+
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => {
+            let snapshots = source();
+            let sockets: Vec<_> = snapshots
+                .iter()
+                .map(|(socket, stats)| (socket.as_str(), *stats))
+                .collect();
+            respond(path, &sockets)
+        }
+        _ => None,
+    };
+    let (status, content_type, body) = match response {
+        Some((content_type, body)) => ("200 OK", content_type, body),
+        None => ("404 Not Found", "text/plain", String::from("not found\n")),
+    };
+    write!(
+        stream,
+        "HTTP/1.0 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<(&'static str, Stats)> {
+        let stats = |pkt_sent_total, mbps_send_rate| Stats {
+            pkt_sent_total,
+            mbps_send_rate,
+            ms_rtt: 12.5,
+            ..Stats::default()
+        };
+        vec![("cam\"1", stats(10, 1.5)), ("cam2", stats(5, 2.0))]
+    }
+
+    #[test]
+    fn renders_prometheus_text() {
+        let text = render_prometheus(&sample());
+        assert!(text.contains("srt_sockets 2\n"));
+        assert!(text.contains("# TYPE srt_ms_rtt gauge\nsrt_ms_rtt{socket=\"cam\\\"1\"} 12.5\n"));
+        assert!(text.contains("srt_mbps_max_bw{socket=\"cam2\"} 0\n"));
+        assert!(text.contains("# TYPE srt_pkt_sent_total counter\n"));
+        assert!(text.contains("srt_pkt_sent_total{socket=\"cam2\"} 5\n"));
+        assert!(!text.contains("srt_time"));
+    }
+
+    #[test]
+    fn renders_json_with_aggregate() {
+        let json = render_json(&sample());
+        assert!(json.starts_with("{\"sockets\":[{\"timestamp\":"));
+        assert!(json.contains("\"socket\":\"cam\\\"1\""));
+        assert!(json.ends_with(",\"byteRecvTotal\":0}}"));
+        assert!(json.contains("\"aggregate\":{\"sockets\":2,\"mbpsSendRate\":3.5,"));
+        assert!(json.contains("\"pktSentTotal\":15,"));
+
+        assert_eq!(
+            respond("/metrics?x=1", &[]).unwrap().0,
+            PROMETHEUS_CONTENT_TYPE
+        );
+        assert_eq!(respond("/", &[]), None);
+    }
+
+    #[cfg(feature = "http-stats")]
+    #[test]
+    fn serves_scrapes_past_a_stalled_client() {
+        use crate::cancel::CancellationToken;
+        use std::io::{Read, Write};
+        use std::net::{TcpListener, TcpStream};
+        use std::thread;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let token = CancellationToken::new();
+        let server = {
+            let token = token.clone();
+            thread::spawn(move || {
+                serve_http(listener, &token, || {
+                    vec![(String::from("cam1"), Stats::default())]
+                })
+            })
+        };
+
+        let stalled = TcpStream::connect(addr).unwrap();
+        let mut client = TcpStream::connect(addr).unwrap();
+        client.write_all(b"GET /metrics HTTP/1.0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+        assert!(response.contains("srt_sockets 1\n"));
+
+        drop(stalled);
+        token.cancel();
+        server.join().unwrap().unwrap();
+    }
+}
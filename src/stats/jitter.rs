@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+/// Estimates inter-arrival jitter as described in RFC 3550, section 6.4.1.
+///
+/// SRT does not report jitter itself. Feed each received message's sender
+/// timestamp (`srctime`) together with its local arrival time, and the
+/// estimator keeps the smoothed mean deviation of the transit time.
+#[derive(Debug, Default, Clone)]
+pub struct JitterEstimator {
+    last: Option<(Duration, Instant)>,
+    jitter_us: f64,
+}
+
+impl JitterEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a message sent at `sent` (sender clock) that arrived at
+    /// `arrival` (local clock), returning the updated jitter.
+    pub fn update(&mut self, sent: Duration, arrival: Instant) -> Duration {
+        if let Some((last_sent, last_arrival)) = self.last {
+            let received = signed_micros(arrival, last_arrival);
+            let transmitted = sent.as_micros() as f64 - last_sent.as_micros() as f64;
+            let d = (received - transmitted).abs();
+            self.jitter_us += (d - self.jitter_us) / 16.0;
+        }
+        self.last = Some((sent, arrival));
+        self.jitter()
+    }
+
+    /// The current jitter estimate.
+    pub fn jitter(&self) -> Duration {
+        Duration::from_micros(self.jitter_us.round() as u64)
+    }
+}
+
+fn signed_micros(later: Instant, earlier: Instant) -> f64 {
+    match later.checked_duration_since(earlier) {
+        Some(d) => d.as_micros() as f64,
+        None => -(earlier.duration_since(later).as_micros() as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_transit_has_no_jitter() {
+        let mut estimator = JitterEstimator::new();
+        let start = Instant::now();
+        for i in 0..10 {
+            let t = Duration::from_millis(i * 20);
+            estimator.update(t, start + t + Duration::from_millis(50));
+        }
+        assert_eq!(estimator.jitter(), Duration::ZERO);
+    }
+
+    #[test]
+    fn variable_transit_converges_towards_deviation() {
+        let mut estimator = JitterEstimator::new();
+        let start = Instant::now();
+        for i in 0..500u64 {
+            let t = Duration::from_millis(i * 20);
+            let transit = Duration::from_millis(if i % 2 == 0 { 40 } else { 50 });
+            estimator.update(t, start + t + transit);
+        }
+        let jitter = estimator.jitter();
+        assert!(jitter > Duration::from_millis(9) && jitter <= Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,87 @@
+//! Connection statistics and the helpers built on them.
+//!
+//! [`Stats`] mirrors the fields of libsrt's `SRT_TRACEBSTATS`. Field names
+//! follow the C names in snake case, so `pktSndDrop` becomes `pkt_snd_drop`.
+
+mod alert;
+mod bitrate;
+mod continuity;
+mod csv;
+mod delay;
+mod expiry;
+mod fields;
+mod gaps;
+mod http;
+mod jitter;
+mod ndjson;
+mod quality;
+mod sizes;
+mod watermark;
+
+pub use alert::{DropAlert, DropDirection, DropWatcher};
+pub use bitrate::{BitrateMeter, BitrateTracker, BitsPerSecond};
+pub use continuity::{ContinuousStats, StatsContinuity};
+pub use csv::StatsCsvWriter;
+pub use delay::OneWayDelay;
+pub use expiry::{TtlExpiry, TtlWatcher};
+pub use gaps::{GapDetector, GapEvent};
+pub(crate) use gaps::{MESSAGE_LAST, SEQUENCE_SPACE};
+#[cfg(feature = "http-stats")]
+pub use http::serve_http;
+pub use http::{
+    render_json, render_prometheus, respond, JSON_CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE,
+};
+pub use jitter::JitterEstimator;
+pub use ndjson::StatsNdjsonWriter;
+pub use quality::{QualityWeights, LOSS_CEILING, RETRANSMISSION_CEILING, RTT_CEILING};
+pub use sizes::{MessageSizes, SizeHistogram, TS_PACKET_SIZE};
+pub use watermark::{BufferLevelEvent, BufferWatermarks, BufferZone};
+
+/// A snapshot of the statistics for a single socket.
+///
+/// `*_total` fields are cumulative since the connection was established, the
+/// remaining counters cover the interval since the previous snapshot, and the
+/// `ms_*`/`mbps_*`/`byte_avail_*` fields are instantaneous.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub ms_timestamp: i64,
+
+    pub pkt_sent_total: i64,
+    pub pkt_recv_total: i64,
+    pub pkt_snd_loss_total: i32,
+    pub pkt_rcv_loss_total: i32,
+    pub pkt_retrans_total: i32,
+    pub pkt_rcv_retrans_total: i32,
+    pub pkt_snd_drop_total: i32,
+    pub pkt_rcv_drop_total: i32,
+    pub byte_sent_total: u64,
+    pub byte_recv_total: u64,
+
+    pub pkt_sent: i64,
+    pub pkt_recv: i64,
+    pub pkt_snd_loss: i32,
+    pub pkt_rcv_loss: i32,
+    pub pkt_retrans: i32,
+    pub pkt_rcv_retrans: i32,
+    pub pkt_snd_drop: i32,
+    pub pkt_rcv_drop: i32,
+    pub byte_sent: u64,
+    pub byte_recv: u64,
+    pub mbps_send_rate: f64,
+    pub mbps_recv_rate: f64,
+
+    pub us_pkt_snd_period: f64,
+    pub pkt_flow_window: i32,
+    pub pkt_congestion_window: i32,
+    pub pkt_flight_size: i32,
+    pub ms_rtt: f64,
+    pub mbps_bandwidth: f64,
+    pub byte_avail_snd_buf: i32,
+    pub byte_avail_rcv_buf: i32,
+    pub mbps_max_bw: f64,
+    pub byte_mss: i32,
+    pub ms_snd_buf: i32,
+    pub ms_rcv_buf: i32,
+    pub ms_snd_tsbpd_delay: i32,
+    pub ms_rcv_tsbpd_delay: i32,
+}
@@ -0,0 +1,104 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::SystemTime;
+
+use super::fields::{fields, rfc3339, Field};
+use super::Stats;
+
+/// Writes statistics snapshots as newline-delimited JSON, one object per
+/// socket per sample, ready for log shippers such as Promtail or Filebeat.
+///
+/// Each object carries a `timestamp` (RFC 3339, UTC), a `socket` label, and
+/// the same fields as [`StatsCsvWriter`](super::StatsCsvWriter) under the same
+/// names.
+pub struct StatsNdjsonWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> StatsNdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Writes a line for `socket` sampled now.
+    pub fn write(&mut self, socket: &str, stats: &Stats) -> io::Result<()> {
+        self.write_at(SystemTime::now(), socket, stats)
+    }
+
+    /// Writes a line for `socket` sampled at `time`.
+    pub fn write_at(&mut self, time: SystemTime, socket: &str, stats: &Stats) -> io::Result<()> {
+        writeln!(self.writer, "{}", to_json(time, socket, stats))
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+pub(crate) fn to_json(time: SystemTime, socket: &str, stats: &Stats) -> String {
+    let mut json = format!(
+        "{{\"timestamp\":\"{}\",\"socket\":{}",
+        rfc3339(time),
+        json_string(socket)
+    );
+    for (name, value) in fields(stats) {
+        match value {
+            Field::Float(value) if !value.is_finite() => write!(json, ",\"{name}\":null"),
+            value => write!(json, ",\"{name}\":{value}"),
+        }
+        .unwrap();
+    }
+    json.push('}');
+    json
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if u32::from(c) < 0x20 => write!(json, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn writes_one_object_per_line() {
+        let mut writer = StatsNdjsonWriter::new(Vec::new());
+        let time = UNIX_EPOCH + Duration::from_secs(1);
+        let stats = Stats {
+            ms_rtt: f64::NAN,
+            pkt_sent: 7,
+            ..Stats::default()
+        };
+        writer.write_at(time, "feed \"a\"", &stats).unwrap();
+        writer.write_at(time, "feed b", &stats).unwrap();
+
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(
+            r#"{"timestamp":"1970-01-01T00:00:01.000Z","socket":"feed \"a\"","Time":0,"#
+        ));
+        assert!(lines[0].contains(r#""msRTT":null,"#));
+        assert!(lines[0].contains(r#""pktSent":7,"#));
+        assert!(lines[0].ends_with('}'));
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use super::Stats;
+
+/// RTT at or above which the RTT component is fully penalised.
+pub const RTT_CEILING: Duration = Duration::from_millis(500);
+/// Loss ratio at or above which the loss component is fully penalised.
+pub const LOSS_CEILING: f64 = 0.10;
+/// Retransmission ratio at or above which that component is fully penalised.
+pub const RETRANSMISSION_CEILING: f64 = 0.25;
+
+/// Relative weights of the components of [`QualityWeights::score`].
+///
+/// Weights are normalised by their sum, so only their ratios matter. A
+/// negative or non-finite weight counts as zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityWeights {
+    pub rtt: f64,
+    pub loss: f64,
+    pub retransmission: f64,
+    pub buffer: f64,
+}
+
+impl Default for QualityWeights {
+    fn default() -> Self {
+        Self {
+            rtt: 0.2,
+            loss: 0.4,
+            retransmission: 0.2,
+            buffer: 0.2,
+        }
+    }
+}
+
+impl QualityWeights {
+    /// Rates the link described by `stats` from 0 (unusable) to 100 (perfect).
+    ///
+    /// Each component is turned into a penalty between 0 and 1:
+    ///
+    /// * RTT: `ms_rtt / RTT_CEILING`, or 0 while the RTT is not finite.
+    /// * Loss: `(pkt_snd_loss + pkt_rcv_loss) / (pkt_sent + pkt_recv)`,
+    ///   divided by [`LOSS_CEILING`].
+    /// * Retransmission: `pkt_retrans / pkt_sent`, divided by
+    ///   [`RETRANSMISSION_CEILING`].
+    /// * Buffer: `ms_snd_buf / ms_snd_tsbpd_delay`, i.e. how much of the
+    ///   latency budget the sender backlog is consuming.
+    ///
+    /// Penalties are clamped to 1, and the score is
+    /// `100 * (1 - Σ weight × penalty / Σ weight)`, rounded. The interval
+    /// counters are used, so the score reflects the latest sampling period.
+    /// Apply it to each member's stats to rank the links of a group.
+    pub fn score(&self, stats: &Stats) -> u8 {
+        // An RTT that is not finite has not been measured.
+        let rtt = if stats.ms_rtt.is_finite() {
+            stats.ms_rtt / RTT_CEILING.as_millis() as f64
+        } else {
+            0.0
+        };
+        let loss = ratio(
+            stats.pkt_snd_loss as f64 + stats.pkt_rcv_loss as f64,
+            stats.pkt_sent as f64 + stats.pkt_recv as f64,
+        ) / LOSS_CEILING;
+        let retransmission =
+            ratio(stats.pkt_retrans as f64, stats.pkt_sent as f64) / RETRANSMISSION_CEILING;
+        let buffer = ratio(stats.ms_snd_buf as f64, stats.ms_snd_tsbpd_delay as f64);
+
+        let components = [
+            (self.rtt, rtt),
+            (self.loss, loss),
+            (self.retransmission, retransmission),
+            (self.buffer, buffer),
+        ];
+        let components = components.map(|(weight, penalty)| {
+            let weight = if weight.is_finite() {
+                weight.max(0.0)
+            } else {
+                0.0
+            };
+            (weight, penalty)
+        });
+        let total: f64 = components.iter().map(|(weight, _)| weight).sum();
+        if total <= 0.0 {
+            return 100;
+        }
+        let penalty: f64 = components
+            .iter()
+            .map(|(weight, penalty)| weight * penalty.clamp(0.0, 1.0))
+            .sum();
+        (100.0 * (1.0 - penalty / total)).round().clamp(0.0, 100.0) as u8
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_link_scores_perfectly() {
+        assert_eq!(QualityWeights::default().score(&Stats::default()), 100);
+    }
+
+    #[test]
+    fn loss_dominates_default_weights() {
+        let stats = Stats {
+            ms_rtt: 50.0,
+            pkt_sent: 1000,
+            pkt_snd_loss: 100,
+            ..Stats::default()
+        };
+        // RTT penalty 0.1 * 0.2 + loss penalty 1.0 * 0.4 = 0.42
+        assert_eq!(QualityWeights::default().score(&stats), 58);
+    }
+
+    #[test]
+    fn invalid_weights_stay_in_range() {
+        let weights = QualityWeights {
+            rtt: -1.0,
+            loss: f64::NAN,
+            retransmission: f64::INFINITY,
+            buffer: 1.0,
+        };
+        let stats = Stats {
+            ms_rtt: 250.0,
+            ..Stats::default()
+        };
+        assert_eq!(weights.score(&stats), 100);
+        let weights = QualityWeights {
+            rtt: 1.0,
+            ..weights
+        };
+        assert_eq!(weights.score(&stats), 75);
+    }
+
+    #[test]
+    fn unmeasured_rtt_and_large_counters_are_harmless() {
+        let stats = Stats {
+            ms_rtt: f64::NAN,
+            pkt_snd_loss: i32::MAX,
+            pkt_rcv_loss: i32::MAX,
+            pkt_sent: i64::MAX,
+            pkt_recv: i64::MAX,
+            ..Stats::default()
+        };
+        assert_eq!(QualityWeights::default().score(&stats), 100);
+    }
+}
@@ -0,0 +1,127 @@
+/// The size of an MPEG-TS packet.
+pub const TS_PACKET_SIZE: usize = 188;
+
+/// Counts messages by size.
+///
+/// Each bucket counts messages no larger than its bound and larger than the
+/// previous one; a final bucket counts messages above the last bound.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeHistogram {
+    bounds: Vec<usize>,
+    counts: Vec<u64>,
+    ts_misaligned: u64,
+}
+
+impl SizeHistogram {
+    /// Creates a histogram with the given ascending upper bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bounds` is not strictly ascending.
+    pub fn new(bounds: Vec<usize>) -> Self {
+        assert!(
+            bounds.windows(2).all(|pair| pair[0] < pair[1]),
+            "bucket bounds must be strictly ascending"
+        );
+        let counts = vec![0; bounds.len() + 1];
+        Self {
+            bounds,
+            counts,
+            ts_misaligned: 0,
+        }
+    }
+
+    /// Buckets at each multiple of [`TS_PACKET_SIZE`] up to the usual seven
+    /// packets per message, followed by the live mode payload limit.
+    pub fn ts_buckets() -> Self {
+        let mut bounds: Vec<usize> = (1..=7).map(|n| n * TS_PACKET_SIZE).collect();
+        bounds.push(crate::copy::LIVE_MAX_PAYLOAD_SIZE);
+        Self::new(bounds)
+    }
+
+    pub fn record(&mut self, len: usize) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|&bound| len <= bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        if len % TS_PACKET_SIZE != 0 {
+            self.ts_misaligned += 1;
+        }
+    }
+
+    pub fn bounds(&self) -> &[usize] {
+        &self.bounds
+    }
+
+    /// Message counts per bucket, one more than there are bounds.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Messages whose size is not a whole number of TS packets, which
+    /// points at a sender splitting the transport stream incorrectly.
+    pub fn ts_misaligned(&self) -> u64 {
+        self.ts_misaligned
+    }
+}
+
+/// Histograms of sent and received message sizes for one socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageSizes {
+    pub sent: SizeHistogram,
+    pub received: SizeHistogram,
+}
+
+impl MessageSizes {
+    /// Tracks both directions with the same bucket bounds.
+    pub fn new(bounds: Vec<usize>) -> Self {
+        Self {
+            sent: SizeHistogram::new(bounds.clone()),
+            received: SizeHistogram::new(bounds),
+        }
+    }
+
+    pub fn record_sent(&mut self, len: usize) {
+        self.sent.record(len);
+    }
+
+    pub fn record_received(&mut self, len: usize) {
+        self.received.record(len);
+    }
+}
+
+impl Default for MessageSizes {
+    /// Tracks both directions with [`SizeHistogram::ts_buckets`].
+    fn default() -> Self {
+        Self {
+            sent: SizeHistogram::ts_buckets(),
+            received: SizeHistogram::ts_buckets(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_sizes_and_flags_misalignment() {
+        let mut sizes = MessageSizes::default();
+        for len in [1316, 1316, 188, 1000, 1500] {
+            sizes.record_received(len);
+        }
+        sizes.record_sent(100);
+
+        let received = &sizes.received;
+        assert_eq!(received.counts(), [1, 0, 0, 0, 0, 1, 2, 0, 1]);
+        assert_eq!(received.total(), 5);
+        assert_eq!(received.ts_misaligned(), 2);
+        assert_eq!(sizes.sent.counts()[0], 1);
+    }
+}
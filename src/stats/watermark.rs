@@ -0,0 +1,125 @@
+use std::time::Instant;
+
+use super::Stats;
+
+/// Where the receiver buffer fill level sits relative to the watermarks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BufferZone {
+    /// At or below the low watermark; playback is close to underrunning.
+    Low,
+    /// Between the two watermarks.
+    Normal,
+    /// At or above the high watermark; the buffer is close to overflowing.
+    High,
+}
+
+/// A crossing of a watermark observed by a [`BufferWatermarks`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BufferLevelEvent {
+    pub from: BufferZone,
+    pub to: BufferZone,
+    /// Fraction of the receiver buffer in use, from 0 to 1.
+    pub fill: f64,
+    pub at: Instant,
+}
+
+/// Reports when the receiver buffer fill level crosses a high or low
+/// watermark, derived from `byteAvailRcvBuf`.
+///
+/// Watermarks are fractions of the buffer size. Only changes of
+/// [`BufferZone`] are reported, so a level hovering beyond a watermark does
+/// not raise an event for every sample.
+pub struct BufferWatermarks {
+    capacity: u64,
+    low: f64,
+    high: f64,
+    zone: Option<BufferZone>,
+    on_event: Box<dyn FnMut(BufferLevelEvent) + Send>,
+}
+
+impl BufferWatermarks {
+    /// Creates a watcher for a receiver buffer of `capacity` bytes, the
+    /// socket's `SRTO_RCVBUF`.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `0 <= low < high <= 1`.
+    pub fn new<F>(capacity: u64, low: f64, high: f64, on_event: F) -> Self
+    where
+        F: FnMut(BufferLevelEvent) + Send + 'static,
+    {
+        assert!(
+            0.0 <= low && low < high && high <= 1.0,
+            "watermarks must satisfy 0 <= low < high <= 1"
+        );
+        Self {
+            capacity,
+            low,
+            high,
+            zone: None,
+            on_event: Box::new(on_event),
+        }
+    }
+
+    /// Feeds a statistics snapshot taken at the current instant.
+    pub fn sample(&mut self, stats: &Stats) {
+        self.sample_at(Instant::now(), stats);
+    }
+
+    /// Feeds a statistics snapshot taken at `at`.
+    pub fn sample_at(&mut self, at: Instant, stats: &Stats) {
+        if self.capacity == 0 {
+            return;
+        }
+        let available = u64::try_from(stats.byte_avail_rcv_buf).unwrap_or(0);
+        let used = self.capacity.saturating_sub(available);
+        let fill = used as f64 / self.capacity as f64;
+        let to = if fill >= self.high {
+            BufferZone::High
+        } else if fill <= self.low {
+            BufferZone::Low
+        } else {
+            BufferZone::Normal
+        };
+        if let Some(from) = self.zone.replace(to).filter(|&from| from != to) {
+            (self.on_event)(BufferLevelEvent { from, to, fill, at });
+        }
+    }
+
+    /// The zone of the most recent sample.
+    pub fn zone(&self) -> Option<BufferZone> {
+        self.zone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn reports_zone_changes() {
+        let (tx, rx) = mpsc::channel();
+        let mut watermarks =
+            BufferWatermarks::new(1000, 0.2, 0.8, move |event| tx.send(event).unwrap());
+        let available = |byte_avail_rcv_buf| Stats {
+            byte_avail_rcv_buf,
+            ..Stats::default()
+        };
+
+        for avail in [500, 400, 150, 100, 500, 900] {
+            watermarks.sample(&available(avail));
+        }
+
+        let events: Vec<_> = rx.try_iter().map(|e| (e.from, e.to)).collect();
+        assert_eq!(
+            events,
+            [
+                (BufferZone::Normal, BufferZone::High),
+                (BufferZone::High, BufferZone::Normal),
+                (BufferZone::Normal, BufferZone::Low),
+            ]
+        );
+        assert_eq!(watermarks.zone(), Some(BufferZone::Low));
+    }
+}
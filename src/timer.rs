@@ -0,0 +1,310 @@
+//! Runtime-agnostic timers.
+//!
+//! Deadlines are driven by a single background thread rather than by an async
+//! runtime, so the futures in this module behave identically under tokio,
+//! async-std, smol, or a hand-rolled executor.
+//!
+//! The crate has no async sockets of its own, so this module offers no
+//! connect, accept or recv deadlines as such: bound those futures with
+//! [`timeout`], or use [`SrtTransport::recv_timeout`] on a blocking
+//! transport.
+//!
+//! [`SrtTransport::recv_timeout`]: crate::transport::SrtTransport::recv_timeout
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct Entries {
+    next_id: u64,
+    /// Wakers keyed by deadline, then by registration order.
+    wakers: BTreeMap<(Instant, u64), Waker>,
+}
+
+struct Timer {
+    entries: Mutex<Entries>,
+    condvar: Condvar,
+}
+
+impl Timer {
+    fn get() -> &'static Timer {
+        static TIMER: OnceLock<Timer> = OnceLock::new();
+        TIMER.get_or_init(|| {
+            thread::Builder::new()
+                .name("as-srt-timer".into())
+                .spawn(|| Timer::get().run())
+                .expect("failed to spawn timer thread");
+            Timer {
+                entries: Mutex::new(Entries::default()),
+                condvar: Condvar::new(),
+            }
+        })
+    }
+
+    /// Wakes `waker` at `deadline`, returning the key to deregister it with.
+    fn register(&self, deadline: Instant, waker: Waker) -> (Instant, u64) {
+        let mut entries = self.entries.lock().unwrap();
+        let earliest = entries.wakers.keys().next().map(|&(deadline, _)| deadline);
+        let key = (deadline, entries.next_id);
+        entries.next_id += 1;
+        entries.wakers.insert(key, waker);
+        if earliest.is_none_or(|earliest| deadline < earliest) {
+            self.condvar.notify_one();
+        }
+        key
+    }
+
+    fn deregister(&self, key: (Instant, u64)) {
+        let waker = self.entries.lock().unwrap().wakers.remove(&key);
+        drop(waker);
+    }
+
+    fn run(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            while let Some(entry) = entries
+                .wakers
+                .first_entry()
+                .filter(|entry| entry.key().0 <= now)
+            {
+                entry.remove().wake();
+            }
+            entries = match entries.wakers.keys().next() {
+                Some(&(deadline, _)) => {
+                    let wait = deadline.saturating_duration_since(now);
+                    self.condvar.wait_timeout(entries, wait).unwrap().0
+                }
+                None => self.condvar.wait(entries).unwrap(),
+            };
+        }
+    }
+}
+
+/// A future that completes once its deadline has passed.
+///
+/// Dropping or resetting a pending delay removes it from the timer.
+#[derive(Debug)]
+pub struct Delay {
+    deadline: Instant,
+    registered: Option<((Instant, u64), Waker)>,
+}
+
+impl Delay {
+    /// The instant at which this delay completes.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Moves the deadline, re-arming the delay if it had already completed.
+    pub fn reset(&mut self, deadline: Instant) {
+        self.deadline = deadline;
+        self.deregister();
+    }
+
+    fn deregister(&mut self) {
+        if let Some((key, _)) = self.registered.take() {
+            Timer::get().deregister(key);
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            self.deregister();
+            return Poll::Ready(());
+        }
+        let registered = self
+            .registered
+            .as_ref()
+            .is_some_and(|(_, waker)| waker.will_wake(cx.waker()));
+        if !registered {
+            self.deregister();
+            let key = Timer::get().register(self.deadline, cx.waker().clone());
+            self.registered = Some((key, cx.waker().clone()));
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Delay {
+    fn drop(&mut self) {
+        self.deregister();
+    }
+}
+
+/// Completes after `duration` has elapsed. A duration too long to represent,
+/// such as [`Duration::MAX`], never completes in practice.
+pub fn sleep(duration: Duration) -> Delay {
+    sleep_until(deadline_after(duration))
+}
+
+/// Completes once `deadline` has been reached.
+pub fn sleep_until(deadline: Instant) -> Delay {
+    Delay {
+        deadline,
+        registered: None,
+    }
+}
+
+/// Error returned when a [`Timeout`] expires before its future completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed(());
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+impl From<Elapsed> for io::Error {
+    fn from(err: Elapsed) -> Self {
+        io::Error::new(io::ErrorKind::TimedOut, err)
+    }
+}
+
+/// A future bounded by a deadline, see [`timeout`].
+#[derive(Debug)]
+pub struct Timeout<F> {
+    future: F,
+    delay: Delay,
+}
+
+impl<F> Timeout<F> {
+    /// Consumes the timeout, returning the underlying future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is never moved out of a pinned `Timeout`, and
+        // `delay` is `Unpin`.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        if let Poll::Ready(output) = future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        match Pin::new(&mut this.delay).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(Elapsed(()))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Requires `future` to complete within `duration`. A duration too long to
+/// represent, such as [`Duration::MAX`], never expires in practice.
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    timeout_at(deadline_after(duration), future)
+}
+
+/// Requires `future` to complete before `deadline`.
+pub fn timeout_at<F: Future>(deadline: Instant, future: F) -> Timeout<F> {
+    Timeout {
+        future,
+        delay: sleep_until(deadline),
+    }
+}
+
+/// The instant `duration` from now, or about a century away if that cannot
+/// be represented.
+pub(crate) fn deadline_after(duration: Duration) -> Instant {
+    const FAR_FUTURE: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+    let now = Instant::now();
+    now.checked_add(duration)
+        .or_else(|| now.checked_add(FAR_FUTURE))
+        .unwrap_or(now)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::future::pending;
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn sleep_waits_for_deadline() {
+        let start = Instant::now();
+        block_on(sleep(Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn timeout_expires() {
+        let result = block_on(timeout(Duration::from_millis(10), pending::<()>()));
+        assert_eq!(result, Err(Elapsed(())));
+    }
+
+    #[test]
+    fn unrepresentable_durations_never_elapse() {
+        let result = block_on(timeout(Duration::MAX, async { 7 }));
+        assert_eq!(result, Ok(7));
+        assert!(sleep(Duration::MAX).deadline() > Instant::now() + Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn timeout_passes_through_output() {
+        let result = block_on(timeout(Duration::from_secs(5), async { 7 }));
+        assert_eq!(result, Ok(7));
+    }
+
+    #[test]
+    fn dropped_delays_leave_the_timer() {
+        let waker = Waker::noop();
+        let mut cx = Context::from_waker(waker);
+        let deadline = Instant::now() + Duration::from_secs(3600);
+        let pending = |timer: &Timer| {
+            let entries = timer.entries.lock().unwrap();
+            entries
+                .wakers
+                .keys()
+                .filter(|&&(at, _)| at == deadline)
+                .count()
+        };
+
+        let mut delay = sleep_until(deadline);
+        assert!(Pin::new(&mut delay).poll(&mut cx).is_pending());
+        assert_eq!(pending(Timer::get()), 1);
+        delay.reset(deadline);
+        assert_eq!(pending(Timer::get()), 0);
+        assert!(Pin::new(&mut delay).poll(&mut cx).is_pending());
+        drop(delay);
+        assert_eq!(pending(Timer::get()), 0);
+    }
+}
@@ -0,0 +1,168 @@
+//! A message-oriented transport abstraction.
+//!
+//! [`SrtTransport`] captures the connect/send/recv surface shared by SRT
+//! sockets, plain UDP, and test doubles, so that higher-level components can
+//! be written once and run over any of them.
+
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::time::Duration;
+
+use crate::counters::counters;
+
+/// A connected, message-oriented transport.
+///
+/// Each successful `send` transmits exactly one message and each successful
+/// `recv` yields exactly one message, so message boundaries are preserved end
+/// to end. A `recv` returning `Ok(0)` signals that the peer has closed.
+///
+/// UDP has no close, so on [`UdpSocket`] `Ok(0)` is an empty datagram, which
+/// the components built on this trait take as the end of the stream. Send
+/// no empty datagrams over plain UDP except to signal the end.
+pub trait SrtTransport {
+    /// Connects to `addr`, returning the connected transport.
+    fn connect(addr: SocketAddr) -> io::Result<Self>
+    where
+        Self: Sized;
+
+    /// Sends `buf` as a single message, returning the number of bytes sent.
+    fn send(&self, buf: &[u8]) -> io::Result<usize>;
+
+    /// Receives a single message into `buf`, returning its length.
+    fn recv(&self, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// Receives a single message into `buf`, waiting at most `timeout` for it
+    /// to arrive. `None` waits indefinitely, and a zero timeout only takes a
+    /// message that has already arrived.
+    ///
+    /// The timeout applies to this call only; the transport's configured
+    /// receive timeout is in effect again for subsequent calls. Expiry is
+    /// reported as [`io::ErrorKind::TimedOut`].
+    fn recv_timeout(&self, buf: &mut [u8], timeout: Option<Duration>) -> io::Result<usize>;
+
+    /// Returns the local address this transport is bound to.
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+
+    /// Returns the address of the connected peer.
+    fn peer_addr(&self) -> io::Result<SocketAddr>;
+}
+
+impl SrtTransport for UdpSocket {
+    fn connect(addr: SocketAddr) -> io::Result<Self> {
+        let local: SocketAddr = match addr {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = UdpSocket::bind(local)?;
+        UdpSocket::connect(&socket, addr)?;
+        Ok(socket)
+    }
+
+    fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        let result = UdpSocket::send(self, buf);
+        counters().record_send(&result);
+        result
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let result = UdpSocket::recv(self, buf);
+        counters().record_recv(&result);
+        result
+    }
+
+    fn recv_timeout(&self, buf: &mut [u8], timeout: Option<Duration>) -> io::Result<usize> {
+        let result = udp_recv_timeout(self, buf, timeout);
+        counters().record_recv(&result);
+        result
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        UdpSocket::local_addr(self)
+    }
+
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        UdpSocket::peer_addr(self)
+    }
+}
+
+/// Borrows the socket-wide receive timeout for the call, restoring the
+/// previous one afterwards, so concurrent receives on a shared socket may
+/// observe it. A zero timeout switches the socket to non-blocking instead.
+fn udp_recv_timeout(
+    socket: &UdpSocket,
+    buf: &mut [u8],
+    timeout: Option<Duration>,
+) -> io::Result<usize> {
+    let (result, restored) = if timeout == Some(Duration::ZERO) {
+        socket.set_nonblocking(true)?;
+        let result = socket.recv(buf);
+        (result, socket.set_nonblocking(false))
+    } else {
+        let previous = socket.read_timeout()?;
+        socket.set_read_timeout(timeout)?;
+        let result = socket.recv(buf);
+        (result, socket.set_read_timeout(previous))
+    };
+    match result {
+        // A datagram is never discarded for a failure to restore the socket.
+        Ok(n) => Ok(n),
+        // Timeouts surface as `WouldBlock` on unix and `TimedOut` on Windows.
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+            restored.and(Err(io::ErrorKind::TimedOut.into()))
+        }
+        Err(e) => restored.and(Err(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn udp_preserves_message_boundaries() {
+        let server = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let client = <UdpSocket as SrtTransport>::connect(server.local_addr().unwrap()).unwrap();
+        SrtTransport::send(&client, b"first").unwrap();
+        SrtTransport::send(&client, b"second").unwrap();
+
+        let mut buf = [0; 64];
+        let (n, _) = server.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"first");
+        let (n, _) = server.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"second");
+    }
+
+    #[test]
+    fn recv_timeout_restores_previous_timeout() {
+        let server = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let client = <UdpSocket as SrtTransport>::connect(server.local_addr().unwrap()).unwrap();
+        let idle = Some(Duration::from_secs(30));
+        client.set_read_timeout(idle).unwrap();
+
+        let mut buf = [0; 16];
+        let err = client
+            .recv_timeout(&mut buf, Some(Duration::from_millis(10)))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(client.read_timeout().unwrap(), idle);
+    }
+
+    #[test]
+    fn zero_timeout_polls() {
+        let server = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let client = <UdpSocket as SrtTransport>::connect(server.local_addr().unwrap()).unwrap();
+        let mut buf = [0; 16];
+        let err = client
+            .recv_timeout(&mut buf, Some(Duration::ZERO))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        server
+            .send_to(b"ready", client.local_addr().unwrap())
+            .unwrap();
+        let n = client
+            .recv_timeout(&mut buf, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(&buf[..n], b"ready");
+    }
+}
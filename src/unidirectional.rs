@@ -0,0 +1,126 @@
+//! Send-only and receive-only transport wrappers.
+//!
+//! A contribution encoder only ever sends and a monitor only ever receives;
+//! wrapping their transports in [`SrtSender`] or [`SrtReceiver`] makes the
+//! other direction a compile error rather than a latent bug.
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::options::{OptionSet, SockOpt};
+use crate::transport::SrtTransport;
+
+/// A transport that can only send.
+#[derive(Debug)]
+pub struct SrtSender<T> {
+    transport: T,
+}
+
+impl<T: SrtTransport> SrtSender<T> {
+    /// Connects a new transport to `addr` for sending.
+    pub fn connect(addr: SocketAddr) -> io::Result<Self> {
+        T::connect(addr).map(Self::new)
+    }
+
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    /// Options marking a socket as the sending side (`SRTO_SENDER`), for
+    /// peers that still negotiate the HSv4 handshake.
+    pub fn options() -> OptionSet {
+        let mut options = OptionSet::new();
+        options
+            .set(SockOpt::Sender, true)
+            .expect("unversioned sets accept every option");
+        options
+    }
+
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.transport.send(buf)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.transport.local_addr()
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.transport.peer_addr()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+}
+
+/// A transport that can only receive.
+#[derive(Debug)]
+pub struct SrtReceiver<T> {
+    transport: T,
+}
+
+impl<T: SrtTransport> SrtReceiver<T> {
+    /// Connects a new transport to `addr` for receiving.
+    pub fn connect(addr: SocketAddr) -> io::Result<Self> {
+        T::connect(addr).map(Self::new)
+    }
+
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    /// Options marking a socket as the receiving side (`SRTO_SENDER` off).
+    pub fn options() -> OptionSet {
+        let mut options = OptionSet::new();
+        options
+            .set(SockOpt::Sender, false)
+            .expect("unversioned sets accept every option");
+        options
+    }
+
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.transport.recv(buf)
+    }
+
+    pub fn recv_timeout(&self, buf: &mut [u8], timeout: Option<Duration>) -> io::Result<usize> {
+        self.transport.recv_timeout(buf, timeout)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.transport.local_addr()
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.transport.peer_addr()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::OptionValue;
+    use std::net::{Ipv4Addr, UdpSocket};
+
+    #[test]
+    fn halves_carry_traffic_one_way() {
+        let receiver = SrtReceiver::new(UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap());
+        let sender: SrtSender<UdpSocket> =
+            SrtSender::connect(receiver.local_addr().unwrap()).unwrap();
+        sender.send(b"frame").unwrap();
+        let mut buf = [0; 8];
+        let n = receiver
+            .recv_timeout(&mut buf, Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(&buf[..n], b"frame");
+
+        assert_eq!(
+            SrtSender::<UdpSocket>::options().get(SockOpt::Sender),
+            Some(&OptionValue::Bool(true))
+        );
+    }
+}